@@ -1,8 +1,15 @@
+use std::cmp::Ordering;
+use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
+// packaging types accepted in `group:artifact:extension:version` coordinates
+const EXTENSIONS: [&str; 10] = [
+    "jar", "zip", "pom", "war", "aar", "txt", "tsrg", "lzma", "json", "xml",
+];
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Artifact {
     pub group: String,
@@ -14,18 +21,62 @@ pub struct Artifact {
 
 impl Artifact {
     pub(crate) fn to_path(&self) -> PathBuf {
+        self.to_path_with_version(&self.version)
+    }
+    /// Path of the artifact stored under a different file version, e.g. a timestamped snapshot.
+    pub(crate) fn to_path_with_version(&self, file_version: &str) -> PathBuf {
         PathBuf::from(format!(
             "{}/{}/{}/{}",
             &self.group_path().to_str().unwrap(),
             &self.artifact,
             &self.version,
-            &self.artifact_filename()
+            &self.artifact_filename(file_version)
+        ))
+    }
+    pub(crate) fn metadata_path(&self) -> PathBuf {
+        PathBuf::from(format!(
+            "{}/{}/{}/maven-metadata.xml",
+            &self.group_path().to_str().unwrap(),
+            &self.artifact,
+            &self.version
         ))
     }
+    /// Reverse of [`Artifact::to_path`], `None` if the path isn't in the maven layout.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let parts: Vec<&str> = path.split('/').collect();
+        if parts.len() < 4 {
+            return None;
+        }
+        let (file, version, artifact) = (
+            parts[parts.len() - 1],
+            parts[parts.len() - 2],
+            parts[parts.len() - 3],
+        );
+        let rest = file.strip_prefix(&format!("{}-{}", artifact, version))?;
+        let dot = rest.rfind('.')?;
+        let classifier = match &rest[..dot] {
+            "" => None,
+            classifier => Some(classifier.strip_prefix('-')?.to_string()),
+        };
+        let extension = match &rest[dot + 1..] {
+            "jar" => None,
+            extension => Some(extension.to_string()),
+        };
+        Some(Artifact {
+            group: parts[..parts.len() - 3].join("."),
+            artifact: artifact.to_string(),
+            version: version.to_string(),
+            classifier,
+            extension,
+        })
+    }
+    pub fn is_snapshot(&self) -> bool {
+        self.version.ends_with("-SNAPSHOT")
+    }
     fn group_path(&self) -> PathBuf {
         PathBuf::from(self.group.replace('.', "/"))
     }
-    fn artifact_filename(&self) -> String {
+    fn artifact_filename(&self, file_version: &str) -> String {
         let classifier_fmt = match self.classifier {
             Some(ref class) => format!("-{classifier}", classifier = class),
             None => "".to_string(),
@@ -37,7 +88,7 @@ impl Artifact {
         format!(
             "{artifact}-{version}{classifier}.{extension}",
             artifact = self.artifact,
-            version = self.version,
+            version = file_version,
             classifier = classifier_fmt,
             extension = extension_fmt
         )
@@ -46,56 +97,267 @@ impl Artifact {
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ArtifactParseError {
-    BadNumberOfParts,
+    BadNumberOfParts(String),
+    EmptyPart(String, &'static str),
+    EmptyExtension(String),
 }
 
-impl ToString for Artifact {
-    fn to_string(&self) -> String {
-        let mut strn = String::new();
-        strn.push_str(&self.group);
-        strn.push(':');
-        strn.push_str(&self.artifact);
-        strn.push(':');
-        strn.push_str(&self.version);
+impl fmt::Display for ArtifactParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactParseError::BadNumberOfParts(s) => write!(
+                f,
+                "Bad artifact coordinate '{}': expected group:artifact[:extension[:classifier]]:version or group:artifact:version[:classifier][@extension]",
+                s
+            ),
+            ArtifactParseError::EmptyPart(s, part) => {
+                write!(f, "Bad artifact coordinate '{}': {} is empty", s, part)
+            }
+            ArtifactParseError::EmptyExtension(s) => {
+                write!(f, "Bad artifact coordinate '{}': extension after '@' is empty", s)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArtifactParseError {}
+
+impl fmt::Display for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
         if let Some(ref classifier) = self.classifier {
-            strn.push(':');
-            strn.push_str(classifier);
+            write!(f, ":{}", classifier)?;
         }
         if let Some(ref ext) = self.extension {
-            strn.push('@');
-            strn.push_str(ext);
+            write!(f, "@{}", ext)?;
         }
-        strn
+        Ok(())
     }
 }
 
 impl FromStr for Artifact {
     type Err = ArtifactParseError;
     fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        let coordinate = s;
         let parts: Vec<&str> = s.split('@').collect();
         let (s, ext): (&str, Option<String>) = match *parts.as_slice() {
+            [_, ""] => return Err(ArtifactParseError::EmptyExtension(coordinate.into())),
             [s, ext] => (s, Some(ext.to_string())),
-            _ => (s, None),
+            [s] => (s, None),
+            _ => return Err(ArtifactParseError::BadNumberOfParts(coordinate.into())),
         };
 
         let parts = s.split(':');
         let parts: Vec<&str> = parts.collect();
-        match *parts.as_slice() {
-            [grp, art, ver] => Ok(Self {
+        let artifact = match *parts.as_slice() {
+            [grp, art, ver] => Self {
                 group: grp.into(),
                 artifact: art.into(),
                 version: ver.into(),
                 classifier: None,
                 extension: ext,
-            }),
-            [grp, art, ver, class] => Ok(Self {
+            },
+            // maven style, the packaging goes before the version
+            [grp, art, packaging, ver] if ext.is_none() && EXTENSIONS.contains(&packaging) => {
+                Self {
+                    group: grp.into(),
+                    artifact: art.into(),
+                    version: ver.into(),
+                    classifier: None,
+                    extension: Some(packaging.into()),
+                }
+            }
+            [grp, art, ver, class] => Self {
                 group: grp.into(),
                 artifact: art.into(),
                 version: ver.into(),
                 classifier: Some(class.into()),
                 extension: ext,
-            }),
-            _ => Err(ArtifactParseError::BadNumberOfParts),
+            },
+            [grp, art, packaging, class, ver] if ext.is_none() => Self {
+                group: grp.into(),
+                artifact: art.into(),
+                version: ver.into(),
+                classifier: Some(class.into()),
+                extension: Some(packaging.into()),
+            },
+            _ => return Err(ArtifactParseError::BadNumberOfParts(coordinate.into())),
+        };
+        let required = [
+            (&artifact.group, "group"),
+            (&artifact.artifact, "artifact"),
+            (&artifact.version, "version"),
+        ];
+        if let Some((_, part)) = required.iter().find(|(v, _)| v.is_empty()) {
+            return Err(ArtifactParseError::EmptyPart(coordinate.into(), part));
+        }
+        if artifact.classifier.as_deref() == Some("") {
+            return Err(ArtifactParseError::EmptyPart(
+                coordinate.into(),
+                "classifier",
+            ));
+        }
+        if artifact.extension.as_deref() == Some("") {
+            return Err(ArtifactParseError::EmptyExtension(coordinate.into()));
+        }
+        Ok(artifact)
+    }
+}
+
+impl Ord for Artifact {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.group
+            .cmp(&other.group)
+            .then_with(|| self.artifact.cmp(&other.artifact))
+            .then_with(|| compare_versions(&self.version, &other.version))
+            .then_with(|| self.classifier.cmp(&other.classifier))
+            .then_with(|| self.extension.cmp(&other.extension))
+    }
+}
+
+impl PartialOrd for Artifact {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares versions by semver when both parse, otherwise segment by segment,
+/// numeric segments are compared as numbers (`5.2` < `5.10`).
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let ordering = match (semver::Version::parse(a), semver::Version::parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => {
+            let a_parts = a.split(&['.', '-', '_'][..]);
+            let mut b_parts = b.split(&['.', '-', '_'][..]);
+            let mut ordering = Ordering::Equal;
+            for a_part in a_parts {
+                let b_part = match b_parts.next() {
+                    Some(b_part) => b_part,
+                    None => {
+                        ordering = Ordering::Greater;
+                        break;
+                    }
+                };
+                ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+                    (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+                    _ => a_part.cmp(b_part),
+                };
+                if ordering != Ordering::Equal {
+                    break;
+                }
+            }
+            if ordering == Ordering::Equal && b_parts.next().is_some() {
+                ordering = Ordering::Less;
+            }
+            ordering
+        }
+    };
+    ordering.then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(version: &str, classifier: Option<&str>, extension: Option<&str>) -> Artifact {
+        Artifact {
+            group: "org.ow2.asm".to_string(),
+            artifact: "asm".to_string(),
+            version: version.to_string(),
+            classifier: classifier.map(str::to_string),
+            extension: extension.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parses_coordinate_forms() {
+        assert_eq!(
+            "org.ow2.asm:asm:9.1".parse(),
+            Ok(artifact("9.1", None, None))
+        );
+        assert_eq!(
+            "org.ow2.asm:asm:zip:9.1".parse(),
+            Ok(artifact("9.1", None, Some("zip")))
+        );
+        assert_eq!(
+            "org.ow2.asm:asm:zip:natives:9.1".parse(),
+            Ok(artifact("9.1", Some("natives"), Some("zip")))
+        );
+        assert_eq!(
+            "org.ow2.asm:asm:9.1:natives@zip".parse(),
+            Ok(artifact("9.1", Some("natives"), Some("zip")))
+        );
+        // a fourth part that isn't a known packaging is a classifier
+        assert_eq!(
+            "org.ow2.asm:asm:9.1:natives".parse(),
+            Ok(artifact("9.1", Some("natives"), None))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_coordinates() {
+        assert_eq!(
+            "org.ow2.asm:asm".parse::<Artifact>(),
+            Err(ArtifactParseError::BadNumberOfParts(
+                "org.ow2.asm:asm".to_string()
+            ))
+        );
+        assert_eq!(
+            "org.ow2.asm:asm:9.1@zip@jar".parse::<Artifact>(),
+            Err(ArtifactParseError::BadNumberOfParts(
+                "org.ow2.asm:asm:9.1@zip@jar".to_string()
+            ))
+        );
+        assert_eq!(
+            "org.ow2.asm::9.1".parse::<Artifact>(),
+            Err(ArtifactParseError::EmptyPart(
+                "org.ow2.asm::9.1".to_string(),
+                "artifact"
+            ))
+        );
+        assert_eq!(
+            "org.ow2.asm:asm:9.1:".parse::<Artifact>(),
+            Err(ArtifactParseError::EmptyPart(
+                "org.ow2.asm:asm:9.1:".to_string(),
+                "classifier"
+            ))
+        );
+        assert_eq!(
+            "org.ow2.asm:asm:9.1@".parse::<Artifact>(),
+            Err(ArtifactParseError::EmptyExtension(
+                "org.ow2.asm:asm:9.1@".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert_eq!(compare_versions("5.2", "5.10"), Ordering::Less);
+        assert_eq!(compare_versions("5.10", "5.2"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2.3", "1.2.10"), Ordering::Less);
+        assert_eq!(compare_versions("5.2", "5.2.1"), Ordering::Less);
+        assert_eq!(compare_versions("9.1", "9.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn path_round_trip() {
+        for original in &[
+            artifact("9.1", None, None),
+            artifact("9.1", Some("natives-linux"), None),
+            artifact("9.1", Some("natives"), Some("zip")),
+        ] {
+            let path = original.to_path();
+            let path = path.to_str().unwrap();
+            assert_eq!(Artifact::from_path(path).as_ref(), Some(original));
         }
+        assert_eq!(
+            artifact("9.1", None, None).to_path().to_str(),
+            Some("org/ow2/asm/asm/9.1/asm-9.1.jar")
+        );
+        assert_eq!(Artifact::from_path("asm/9.1/asm-9.1.jar"), None);
+        assert_eq!(
+            Artifact::from_path("org/ow2/asm/asm/9.1/other-9.1.jar"),
+            None
+        );
     }
 }
@@ -0,0 +1,342 @@
+use crate::artifact::Artifact;
+use crate::cache::CacheScope;
+use crate::download::DownloadPool;
+use crate::error::{GeneratorError, Result};
+use crate::generator::{
+    generate_profile, Settings, DEFAULT_UPDATE_EXCLUSION, DEFAULT_UPDATE_VERIFY,
+};
+use crate::lock::{GenerationInputs, Lockfile};
+use crate::maven::Resolver;
+use crate::minecraft::fabric::supports_game_version;
+use crate::minecraft::forge::ForgeManifest;
+use crate::minecraft::liteloader::LiteLoaderManifest;
+use crate::minecraft::runtime::DEFAULT_RUNTIME_PLATFORMS;
+use crate::minecraft::version::Libraries;
+use crate::minecraft::{Addons, GameType};
+use crate::plan::Plan;
+use std::path::PathBuf;
+
+/// Mod loader of a profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Loader {
+    Vanilla,
+    Forge(String),
+    Fabric(String),
+}
+
+/// Builder of a profile generation, e.g.
+/// `ProfileGenerator::new().name("survival").version("1.16.5").loader(Loader::Forge(..)).server("localhost", 25565).generate()`.
+pub struct ProfileGenerator {
+    name: Option<String>,
+    version: Option<String>,
+    loader: Loader,
+    liteloader: Option<String>,
+    optifine: Option<PathBuf>,
+    address: String,
+    port: u32,
+    output: Option<PathBuf>,
+    repositories: Vec<String>,
+    resolver: Option<Resolver>,
+    pool: Option<DownloadPool>,
+    extra_libraries: Vec<Artifact>,
+    jvm_args: Vec<String>,
+    mods: Vec<PathBuf>,
+    update_verify: Vec<String>,
+    update_exclusion: Vec<String>,
+    runtime_platforms: Vec<String>,
+    runtime_archives: Option<PathBuf>,
+    cache: Option<PathBuf>,
+    dry_run: bool,
+    locked: bool,
+}
+
+impl Default for ProfileGenerator {
+    fn default() -> Self {
+        ProfileGenerator {
+            name: None,
+            version: None,
+            loader: Loader::Vanilla,
+            liteloader: None,
+            optifine: None,
+            address: "localhost".to_string(),
+            port: 25565,
+            output: None,
+            repositories: Vec::new(),
+            resolver: None,
+            pool: None,
+            extra_libraries: Vec::new(),
+            jvm_args: Vec::new(),
+            mods: Vec::new(),
+            update_verify: DEFAULT_UPDATE_VERIFY
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            update_exclusion: DEFAULT_UPDATE_EXCLUSION
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            runtime_platforms: DEFAULT_RUNTIME_PLATFORMS
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            runtime_archives: None,
+            cache: None,
+            dry_run: false,
+            locked: false,
+        }
+    }
+}
+
+impl ProfileGenerator {
+    pub fn new() -> Self {
+        ProfileGenerator::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Minecraft version.
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    pub fn loader(mut self, loader: Loader) -> Self {
+        self.loader = loader;
+        self
+    }
+
+    pub fn liteloader(mut self, version: &str) -> Self {
+        self.liteloader = Some(version.to_string());
+        self
+    }
+
+    /// OptiFine installer jar.
+    pub fn optifine(mut self, installer: PathBuf) -> Self {
+        self.optifine = Some(installer);
+        self
+    }
+
+    pub fn server(mut self, address: &str, port: u32) -> Self {
+        self.address = address.to_string();
+        self.port = port;
+        self
+    }
+
+    /// Folder with shared libraries, assets and natives, defaults to a folder named after the profile.
+    pub fn output(mut self, output: PathBuf) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Maven repository to look up libraries in, repositories are tried in the order they are added.
+    pub fn repository(mut self, url: &str) -> Self {
+        if !self.repositories.iter().any(|v| v == url) {
+            self.repositories.push(url.to_string());
+        }
+        self
+    }
+
+    /// Resolver shared with other profiles, repositories added to this builder are used with it.
+    pub fn resolver(mut self, resolver: Resolver) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Download pool shared with other profiles.
+    pub fn pool(mut self, pool: DownloadPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    pub fn extra_library(mut self, artifact: Artifact) -> Self {
+        if !self.extra_libraries.contains(&artifact) {
+            self.extra_libraries.push(artifact);
+        }
+        self
+    }
+
+    pub fn jvm_arg(mut self, arg: &str) -> Self {
+        self.jvm_args.push(arg.to_string());
+        self
+    }
+
+    /// Mod jar copied to the profile `mods` folder.
+    pub fn mod_file(mut self, path: PathBuf) -> Self {
+        self.mods.push(path);
+        self
+    }
+
+    pub fn update_verify(mut self, paths: Vec<String>) -> Self {
+        self.update_verify = paths;
+        self
+    }
+
+    pub fn update_exclusion(mut self, paths: Vec<String>) -> Self {
+        self.update_exclusion = paths;
+        self
+    }
+
+    /// Platforms to bundle the Java runtime of the version for, none disables bundling.
+    pub fn runtime_platforms(mut self, platforms: Vec<String>) -> Self {
+        self.runtime_platforms = platforms;
+        self
+    }
+
+    /// Folder with `<component>-<platform>.zip` runtime archives, used instead of Mojang downloads.
+    pub fn runtime_archives(mut self, folder: PathBuf) -> Self {
+        self.runtime_archives = Some(folder);
+        self
+    }
+
+    /// Metadata cache folder of this generation, instead of `PROFILE_GENERATOR_CACHE`.
+    pub fn cache(mut self, folder: PathBuf) -> Self {
+        self.cache = Some(folder);
+        self
+    }
+
+    /// Only resolve the plan, nothing is downloaded or written.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Require the resolved profile to match its existing lockfile.
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    pub fn generate(self) -> Result<Plan> {
+        let _cache = self.cache.clone().map(CacheScope::new);
+        let name = self
+            .name
+            .ok_or_else(|| GeneratorError::config("Profile", "name is not set"))?;
+        let version = self
+            .version
+            .ok_or_else(|| GeneratorError::config(&name, "Minecraft version is not set"))?;
+        // OptiFine and LiteLoader need LaunchWrapper, Fabric has its own main class
+        if matches!(self.loader, Loader::Fabric(_))
+            && (self.optifine.is_some() || self.liteloader.is_some())
+        {
+            return Err(GeneratorError::config(
+                format!("Profile {}", name),
+                "fabric can't be used together with optifine or liteloader",
+            ));
+        }
+        let manifest = version.parse::<Libraries>()?;
+        let game_type = match &self.loader {
+            Loader::Vanilla => GameType::Vanilla,
+            Loader::Forge(forge) => {
+                let forge_manifest: ForgeManifest = forge.parse()?;
+                check_required("forge", forge, &version, forge_manifest.minecraft_version())?;
+                GameType::Forge(forge_manifest)
+            }
+            Loader::Fabric(fabric) => {
+                check_fabric(fabric, &version)?;
+                GameType::Fabric(fabric.parse()?)
+            }
+        };
+        let liteloader = match &self.liteloader {
+            Some(liteloader) => {
+                let liteloader_manifest: LiteLoaderManifest = liteloader.parse()?;
+                check_required(
+                    "liteloader",
+                    liteloader,
+                    &version,
+                    liteloader_manifest.minecraft_version(),
+                )?;
+                Some(liteloader_manifest)
+            }
+            None => None,
+        };
+        let optifine = match &self.optifine {
+            Some(path) => Some(path.to_string_lossy().parse()?),
+            None => None,
+        };
+        let inputs = GenerationInputs {
+            liteloader: self.liteloader.clone(),
+            optifine: self.optifine.clone(),
+            repositories: self.repositories.clone(),
+            extra_libraries: self.extra_libraries.iter().map(|v| v.to_string()).collect(),
+            jvm_args: self.jvm_args.clone(),
+            mods: self.mods.clone(),
+            runtime_platforms: self.runtime_platforms.clone(),
+            runtime_archives: self.runtime_archives.clone(),
+        };
+        let output = self.output.unwrap_or_else(|| PathBuf::from(&name));
+        let locked = if self.locked {
+            Some(Lockfile::read(&output.join(&name))?)
+        } else {
+            None
+        };
+        let resolver = match (self.resolver, self.repositories.is_empty()) {
+            (Some(resolver), true) => resolver,
+            (Some(resolver), false) => resolver.with_repositories(self.repositories),
+            (None, true) => Resolver::default(),
+            (None, false) => Resolver::new(self.repositories),
+        };
+        let settings = Settings {
+            output,
+            address: self.address,
+            port: self.port,
+            resolver,
+            pool: self.pool.unwrap_or_default(),
+            extra_libraries: self.extra_libraries,
+            update_verify: self.update_verify,
+            update_exclusion: self.update_exclusion,
+            jvm_args: self.jvm_args,
+            mods: self.mods,
+            runtime_platforms: self.runtime_platforms,
+            runtime_archives: self.runtime_archives,
+            dry_run: self.dry_run,
+            locked,
+            inputs,
+        };
+        generate_profile(
+            &name,
+            &version,
+            manifest,
+            game_type,
+            Addons {
+                liteloader,
+                optifine,
+            },
+            &settings,
+        )
+    }
+}
+
+/// Refuses a loader whose metadata requires another Minecraft version.
+fn check_required(
+    component: &str,
+    version: &str,
+    game_version: &str,
+    required: Option<&str>,
+) -> Result<()> {
+    match required {
+        Some(required) if required != game_version => Err(GeneratorError::IncompatibleVersion {
+            component: component.to_string(),
+            version: version.to_string(),
+            game_version: game_version.to_string(),
+            required: Some(required.to_string()),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Fabric loader versions work with any Minecraft version Fabric has mappings for.
+/// Offline the cached Fabric metadata is used, without it the check fails.
+fn check_fabric(version: &str, game_version: &str) -> Result<()> {
+    if supports_game_version(game_version)? {
+        return Ok(());
+    }
+    Err(GeneratorError::IncompatibleVersion {
+        component: "fabric".to_string(),
+        version: version.to_string(),
+        game_version: game_version.to_string(),
+        required: None,
+    })
+}
@@ -0,0 +1,103 @@
+use crate::error::Result;
+use crate::util::get_optional_text;
+use std::env;
+use std::fs::{create_dir_all, read_to_string, rename, write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Environment variable that overrides the metadata cache folder.
+pub const CACHE_ENV: &str = "PROFILE_GENERATOR_CACHE";
+
+// folder of the running generation, see [`CacheScope`]
+static SCOPED_FOLDER: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Cache folder used by the generation that holds the scope, the previous folder
+/// is restored when it is dropped.
+pub struct CacheScope {
+    previous: Option<PathBuf>,
+}
+
+impl CacheScope {
+    pub fn new(folder: PathBuf) -> Self {
+        let previous = SCOPED_FOLDER.lock().unwrap().replace(folder);
+        CacheScope { previous }
+    }
+}
+
+impl Drop for CacheScope {
+    fn drop(&mut self) {
+        *SCOPED_FOLDER.lock().unwrap() = self.previous.take();
+    }
+}
+
+/// Folder with fetched metadata, laid out like the urls it came from.
+/// A [`CacheScope`] folder wins over `PROFILE_GENERATOR_CACHE`.
+pub fn cache_folder() -> PathBuf {
+    if let Some(folder) = SCOPED_FOLDER.lock().unwrap().clone() {
+        return folder;
+    }
+    if let Some(folder) = env::var_os(CACHE_ENV) {
+        return PathBuf::from(folder);
+    }
+    env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|v| PathBuf::from(v).join(".cache")))
+        .unwrap_or_else(env::temp_dir)
+        .join("profile-generator")
+}
+
+fn cache_path(url: &str) -> PathBuf {
+    let path = url.splitn(2, "://").last().unwrap_or(url);
+    path.split('/')
+        .filter(|v| !v.is_empty() && *v != "..")
+        .fold(cache_folder(), |folder, v| folder.join(v))
+}
+
+fn store(path: &Path, text: &str) {
+    // the cache is best effort, generation doesn't depend on it
+    let partial = path.with_extension("part");
+    let stored = path
+        .parent()
+        .map_or(Ok(()), create_dir_all)
+        .and_then(|_| write(&partial, text))
+        .and_then(|_| rename(&partial, path));
+    if let Err(err) = stored {
+        log::debug!("Can't cache {}: {}", path.display(), err);
+    }
+}
+
+/// Metadata that never changes under the same url, e.g. a loader version JSON.
+/// It is fetched once and then always read from the cache.
+pub fn get_immutable_text(url: &str) -> Result<Option<String>> {
+    let path = cache_path(url);
+    if let Ok(text) = read_to_string(&path) {
+        log::debug!("Use cached {}", url);
+        return Ok(Some(text));
+    }
+    let text = get_optional_text(url)?;
+    if let Some(text) = &text {
+        store(&path, text);
+    }
+    Ok(text)
+}
+
+/// Metadata that changes over time, e.g. the version list.
+/// It is fetched every time, the cached copy is only used when the request fails.
+pub fn get_latest_text(url: &str) -> Result<Option<String>> {
+    let path = cache_path(url);
+    match get_optional_text(url) {
+        Ok(text) => {
+            if let Some(text) = &text {
+                store(&path, text);
+            }
+            Ok(text)
+        }
+        Err(err) => match read_to_string(&path) {
+            Ok(text) => {
+                log::warn!("{}, using cached metadata", err);
+                Ok(Some(text))
+            }
+            Err(_) => Err(err),
+        },
+    }
+}
@@ -0,0 +1,145 @@
+use crate::artifact::Artifact;
+use crate::builder::{Loader, ProfileGenerator};
+use crate::error::{GeneratorError, Result};
+use crate::generator::{DEFAULT_UPDATE_EXCLUSION, DEFAULT_UPDATE_VERIFY};
+use crate::minecraft::runtime::DEFAULT_RUNTIME_PLATFORMS;
+use serde::Deserialize;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// Profile definition read from `profile.toml`, an alternative to command line flags.
+#[derive(Deserialize)]
+pub struct ProfileConfig {
+    pub name: String,
+    pub version: String,
+    pub address: String,
+    pub port: u32,
+    pub forge: Option<String>,
+    pub fabric: Option<String>,
+    pub liteloader: Option<String>,
+    /// OptiFine installer jar.
+    pub optifine: Option<PathBuf>,
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    #[serde(default)]
+    pub repositories: Vec<String>,
+    #[serde(default)]
+    pub extra_libraries: Vec<String>,
+    /// Mod jars copied to the profile `mods` folder.
+    #[serde(default)]
+    pub mods: Vec<PathBuf>,
+    #[serde(default)]
+    pub update: UpdateRules,
+    /// Platforms to bundle the Java runtime for, an empty list disables bundling.
+    #[serde(default = "default_runtime_platforms")]
+    pub runtime_platforms: Vec<String>,
+    /// Folder with `<component>-<platform>.zip` runtime archives.
+    pub runtime_archives: Option<PathBuf>,
+    /// Metadata cache folder.
+    pub cache: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateRules {
+    #[serde(default = "default_verify")]
+    pub verify: Vec<String>,
+    #[serde(default = "default_exclusion")]
+    pub exclusion: Vec<String>,
+}
+
+impl Default for UpdateRules {
+    fn default() -> Self {
+        UpdateRules {
+            verify: default_verify(),
+            exclusion: default_exclusion(),
+        }
+    }
+}
+
+fn default_verify() -> Vec<String> {
+    DEFAULT_UPDATE_VERIFY
+        .iter()
+        .map(|v| v.to_string())
+        .collect()
+}
+
+fn default_runtime_platforms() -> Vec<String> {
+    DEFAULT_RUNTIME_PLATFORMS
+        .iter()
+        .map(|v| v.to_string())
+        .collect()
+}
+
+fn default_exclusion() -> Vec<String> {
+    DEFAULT_UPDATE_EXCLUSION
+        .iter()
+        .map(|v| v.to_string())
+        .collect()
+}
+
+impl ProfileConfig {
+    /// Reads the config, relative paths in it are resolved against the config folder.
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = read_to_string(path).map_err(|e| GeneratorError::io(path, e))?;
+        let mut config: ProfileConfig =
+            toml::from_str(&text).map_err(|e| GeneratorError::config(path.display(), e))?;
+        config.resolve_paths(path.parent().unwrap_or_else(|| Path::new("")));
+        Ok(config)
+    }
+
+    /// Makes relative OptiFine, mod, runtime archive and cache paths relative to `folder`.
+    pub fn resolve_paths(&mut self, folder: &Path) {
+        self.optifine = self.optifine.as_ref().map(|v| folder.join(v));
+        self.mods = self.mods.iter().map(|v| folder.join(v)).collect();
+        self.runtime_archives = self.runtime_archives.as_ref().map(|v| folder.join(v));
+        self.cache = self.cache.as_ref().map(|v| folder.join(v));
+    }
+
+    /// Generator of the profile, output folder and run mode are up to the caller.
+    pub fn generator(&self) -> Result<ProfileGenerator> {
+        let loader = match (&self.forge, &self.fabric) {
+            (Some(_), Some(_)) => {
+                return Err(GeneratorError::config(
+                    format!("Profile {}", self.name),
+                    "forge and fabric can't be used together",
+                ))
+            }
+            (Some(forge), _) => Loader::Forge(forge.to_string()),
+            (_, Some(fabric)) => Loader::Fabric(fabric.to_string()),
+            _ => Loader::Vanilla,
+        };
+        let mut generator = ProfileGenerator::new()
+            .name(&self.name)
+            .version(&self.version)
+            .loader(loader)
+            .server(&self.address, self.port)
+            .update_verify(self.update.verify.clone())
+            .update_exclusion(self.update.exclusion.clone())
+            .runtime_platforms(self.runtime_platforms.clone());
+        if let Some(version) = &self.liteloader {
+            generator = generator.liteloader(version);
+        }
+        if let Some(path) = &self.optifine {
+            generator = generator.optifine(path.clone());
+        }
+        if let Some(folder) = &self.runtime_archives {
+            generator = generator.runtime_archives(folder.clone());
+        }
+        if let Some(folder) = &self.cache {
+            generator = generator.cache(folder.clone());
+        }
+        for repository in &self.repositories {
+            generator = generator.repository(repository);
+        }
+        for library in &self.extra_libraries {
+            generator = generator.extra_library(library.parse::<Artifact>()?);
+        }
+        for arg in &self.jvm_args {
+            generator = generator.jvm_arg(arg);
+        }
+        for path in &self.mods {
+            generator = generator.mod_file(path.clone());
+        }
+        Ok(generator)
+    }
+}
@@ -1,66 +1,175 @@
-use std::fs::{create_dir_all, File};
+use std::fs::{copy, create_dir_all, remove_file, rename, File};
 use std::path::Path;
 
 use std::{io, thread};
 
+use reqwest::blocking::Client;
 use reqwest::StatusCode;
+use sha1::{Digest, Sha1};
 
-pub fn download_file(url: &str, path: &str) {
-    create_dir_all(path).unwrap();
-    let url_parts: Vec<&str> = url.split('/').collect();
-    let output = Path::new(path).join(url_parts.last().unwrap());
-    match reqwest::blocking::get(url) {
-        Ok(mut resp) => {
-            match resp.status() {
-                StatusCode::OK => (),
-                _ => {
-                    println!("Could not download this file: {}", url);
-                    return;
-                }
-            }
-            let mut file = match File::create(&output) {
-                Ok(f) => f,
-                Err(err) => {
-                    println!(
-                        "Error occurred while creating file: {} | Error: {}",
-                        output.display(),
-                        err
-                    );
-                    return;
+use crate::error::{GeneratorError, Result};
+use crate::maven::is_remote;
+use crate::plan::PlannedFile;
+use crate::util::get_optional_text;
+
+/// Downloads with one HTTP client on a fixed number of threads, clones share the client
+/// so a batch of profiles reuses its connections.
+#[derive(Clone)]
+pub struct DownloadPool {
+    client: Client,
+    workers: usize,
+}
+
+impl Default for DownloadPool {
+    fn default() -> Self {
+        DownloadPool::new(4)
+    }
+}
+
+impl DownloadPool {
+    pub fn new(workers: usize) -> Self {
+        DownloadPool {
+            client: Client::new(),
+            workers: workers.max(1),
+        }
+    }
+
+    /// Downloads `file` to `base/<file.path>`, the result is checked against the planned SHA-1 and size.
+    /// An existing file is kept only if it matches them, local files are always copied again.
+    pub fn download(&self, base: &Path, file: &PlannedFile) -> Result<()> {
+        download_file(&self.client, base, file)
+    }
+
+    /// Downloads `files` one after another.
+    pub fn download_single(&self, base: &Path, files: &[PlannedFile]) -> Result<()> {
+        for file in files {
+            self.download(base, file)?;
+        }
+        Ok(())
+    }
+
+    /// Downloads `files` on the pool threads, every thread finishes before the first error is returned.
+    pub fn download_all(&self, base: &Path, files: &[PlannedFile]) -> Result<()> {
+        let mut threads = Vec::new();
+        for worker in 0..self.workers.min(files.len()) {
+            let base = base.to_path_buf();
+            let chunk: Vec<PlannedFile> = files
+                .iter()
+                .skip(worker)
+                .step_by(self.workers)
+                .cloned()
+                .collect();
+            let client = self.client.clone();
+            threads.push(thread::spawn(move || -> Result<()> {
+                for file in &chunk {
+                    download_file(&client, &base, file)?;
                 }
-            };
-            match io::copy(&mut resp, &mut file) {
-                Ok(_) => {} //println!("File {} has been downloaded", output.display()),
-                Err(err) => println!("Could not download this file: {} | Error: {}", url, err),
+                Ok(())
+            }));
+        }
+        let mut result = Ok(());
+        for thread in threads {
+            let downloaded = thread.join().expect("Download thread panicked");
+            if result.is_ok() {
+                result = downloaded;
             }
         }
+        result
+    }
+}
 
-        Err(err) => println!("Could not download this file: {} | Error: {}", url, err),
+fn download_file(client: &Client, base: &Path, file: &PlannedFile) -> Result<()> {
+    let output = base.join(&file.path);
+    let remote = is_remote(&file.url);
+    let published;
+    let file = if remote && file.sha1.is_none() {
+        published = PlannedFile {
+            sha1: published_sha1(&file.url),
+            ..file.clone()
+        };
+        &published
+    } else {
+        file
     };
+    let known = file.sha1.is_some() || file.size.is_some();
+    if remote && known && output.is_file() && check_file(&output, file).is_ok() {
+        log::debug!("File {} is up to date", output.display());
+        return Ok(());
+    }
+    if let Some(parent) = output.parent() {
+        create_dir_all(parent).map_err(|e| GeneratorError::io(parent, e))?;
+    }
+    // write to a temporary file first, so an interrupted or broken download never looks complete
+    let mut partial = output.clone().into_os_string();
+    partial.push(".part");
+    let partial = Path::new(&partial);
+    if remote {
+        fetch(client, &file.url, partial)?;
+    } else {
+        // local repository mirror or mod jar
+        let source = file.url.trim_start_matches("file://");
+        copy(source, partial).map_err(|e| GeneratorError::io(source, e))?;
+    }
+    if let Err(err) = check_file(partial, file) {
+        let _ = remove_file(partial);
+        return Err(err);
+    }
+    rename(partial, &output).map_err(|e| GeneratorError::io(&output, e))
 }
 
-pub fn download_files_single(download: &Vec<(String, String)>) {
-    for file in download {
-        download_file(&file.0, &file.1)
+/// SHA-1 a maven repository publishes next to an artifact, `None` if there is none.
+fn published_sha1(url: &str) -> Option<String> {
+    let text = get_optional_text(&format!("{}.sha1", url)).ok()??;
+    text.split_whitespace()
+        .next()
+        .filter(|v| v.len() == 40 && v.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_lowercase)
+}
+
+fn fetch(client: &Client, url: &str, output: &Path) -> Result<()> {
+    let mut resp = client
+        .get(url)
+        .send()
+        .map_err(|e| GeneratorError::network(url, e))?;
+    if resp.status() != StatusCode::OK {
+        return Err(GeneratorError::network(url, resp.status()));
     }
+    let mut file = File::create(output).map_err(|e| GeneratorError::io(output, e))?;
+    io::copy(&mut resp, &mut file).map_err(|e| GeneratorError::network(url, e))?;
+    Ok(())
 }
 
-pub fn download_files_concurrent(download: &Vec<(String, String)>) {
-    let workers: usize = 4;
-    let chunks = download.chunks(workers);
-    let mut threads = Vec::new();
-    for chunk in chunks {
-        let chunk: Vec<(String, String)> = chunk
-            .iter()
-            .map(|v| (v.0.to_string(), v.1.to_string()))
-            .collect();
-        threads.push(thread::spawn(move || {
-            for file in chunk {
-                download_file(&file.0, &file.1)
-            }
-        }));
+/// Fails if the file at `path` doesn't have the size and SHA-1 `file` expects.
+pub fn check_file(path: &Path, file: &PlannedFile) -> Result<()> {
+    if let Some(expected) = file.size {
+        let actual = path
+            .metadata()
+            .map_err(|e| GeneratorError::io(path, e))?
+            .len();
+        if actual != expected {
+            return Err(GeneratorError::SizeMismatch {
+                path: file.path.to_string(),
+                expected,
+                actual,
+            });
+        }
     }
-    for thread in threads {
-        thread.join().expect("Can't download files");
+    if let Some(expected) = &file.sha1 {
+        let actual = file_sha1(path)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(GeneratorError::HashMismatch {
+                path: file.path.to_string(),
+                expected: expected.to_string(),
+                actual,
+            });
+        }
     }
+    Ok(())
+}
+
+pub fn file_sha1(path: &Path) -> Result<String> {
+    let mut file = File::open(path).map_err(|e| GeneratorError::io(path, e))?;
+    let mut sha1 = Sha1::new();
+    io::copy(&mut file, &mut sha1).map_err(|e| GeneratorError::io(path, e))?;
+    Ok(hex::encode(sha1.finalize()))
 }
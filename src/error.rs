@@ -0,0 +1,188 @@
+use crate::artifact::ArtifactParseError;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, GeneratorError>;
+
+/// Error of profile generation, each variant names the url, file or library that failed.
+#[derive(Debug)]
+pub enum GeneratorError {
+    /// Metadata that can't be parsed or lacks something the generator needs.
+    Metadata {
+        source: String,
+        reason: String,
+    },
+    /// Request that failed or returned an error status.
+    Network {
+        url: String,
+        reason: String,
+    },
+    HashMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+    SizeMismatch {
+        path: String,
+        expected: u64,
+        actual: u64,
+    },
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    ArtifactParse(ArtifactParseError),
+    /// Game or loader version that doesn't exist.
+    UnsupportedVersion {
+        component: String,
+        version: String,
+    },
+    /// Loader version built for another Minecraft version.
+    IncompatibleVersion {
+        component: String,
+        version: String,
+        game_version: String,
+        required: Option<String>,
+    },
+    /// Library that no repository serves.
+    LibraryNotFound(String),
+    /// Profile definition, workspace or lockfile that can't be used.
+    Config {
+        source: String,
+        reason: String,
+    },
+    /// Resolved profile that differs from its lockfile.
+    LockMismatch(String),
+    /// File or metadata whose hash changed since the lockfile was written.
+    LockDrift {
+        source: String,
+        locked: String,
+        actual: String,
+    },
+    /// External installer step that failed, e.g. the OptiFine patcher.
+    Install {
+        component: String,
+        reason: String,
+    },
+}
+
+impl GeneratorError {
+    pub fn io(path: impl AsRef<Path>, error: io::Error) -> Self {
+        GeneratorError::Io {
+            path: path.as_ref().to_path_buf(),
+            error,
+        }
+    }
+
+    pub fn network(url: &str, reason: impl fmt::Display) -> Self {
+        GeneratorError::Network {
+            url: url.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn metadata(source: impl fmt::Display, reason: impl fmt::Display) -> Self {
+        GeneratorError::Metadata {
+            source: source.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn config(source: impl fmt::Display, reason: impl fmt::Display) -> Self {
+        GeneratorError::Config {
+            source: source.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn unsupported_version(component: &str, version: &str) -> Self {
+        GeneratorError::UnsupportedVersion {
+            component: component.to_string(),
+            version: version.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratorError::Metadata { source, reason } => {
+                write!(f, "Bad metadata {}: {}", source, reason)
+            }
+            GeneratorError::Network { url, reason } => {
+                write!(f, "Can't download {}: {}", url, reason)
+            }
+            GeneratorError::HashMismatch {
+                path,
+                expected,
+                actual,
+            } => write!(f, "{} has SHA-1 {}, expected {}", path, actual, expected),
+            GeneratorError::SizeMismatch {
+                path,
+                expected,
+                actual,
+            } => write!(f, "{} has {} bytes, expected {}", path, actual, expected),
+            GeneratorError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            GeneratorError::ArtifactParse(error) => write!(f, "{}", error),
+            GeneratorError::UnsupportedVersion { component, version } => {
+                write!(f, "Unsupported {} version {}", component, version)
+            }
+            GeneratorError::IncompatibleVersion {
+                component,
+                version,
+                game_version,
+                required: Some(required),
+            } => write!(
+                f,
+                "{} {} requires Minecraft {}, not {}",
+                component, version, required, game_version
+            ),
+            GeneratorError::IncompatibleVersion {
+                component,
+                version,
+                game_version,
+                required: None,
+            } => write!(
+                f,
+                "{} {} doesn't support Minecraft {}",
+                component, version, game_version
+            ),
+            GeneratorError::LibraryNotFound(library) => {
+                write!(f, "Library {} not found in any repository", library)
+            }
+            GeneratorError::Config { source, reason } => write!(f, "{}: {}", source, reason),
+            GeneratorError::LockMismatch(reason) => {
+                write!(f, "Profile doesn't match its lockfile: {}", reason)
+            }
+            GeneratorError::LockDrift {
+                source,
+                locked,
+                actual,
+            } => write!(
+                f,
+                "{} changed since it was locked: SHA-1 {}, locked {}",
+                source, actual, locked
+            ),
+            GeneratorError::Install { component, reason } => {
+                write!(f, "Can't install {}: {}", component, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GeneratorError::Io { error, .. } => Some(error),
+            GeneratorError::ArtifactParse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ArtifactParseError> for GeneratorError {
+    fn from(error: ArtifactParseError) -> Self {
+        GeneratorError::ArtifactParse(error)
+    }
+}
@@ -0,0 +1,57 @@
+use crate::error::{GeneratorError, Result};
+use crate::index::{profile_files, read_profiles_in, relative};
+use crate::update::{library_files, remove_empty_parents};
+use std::collections::{BTreeSet, HashSet};
+use std::fs::remove_file;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Files under `libraries` and the asset folders that no profile in `base` references.
+/// Returned paths are relative to `base`, together with the folder they were found in.
+/// Fails if any profile or index in `base` can't be read.
+pub fn unreferenced_files(base: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let profiles = read_profiles_in(base)?;
+    if profiles.is_empty() {
+        // without profiles everything would be collected
+        return Err(GeneratorError::config(base.display(), "no profiles found"));
+    }
+    let mut referenced = HashSet::new();
+    let mut roots = BTreeSet::new();
+    roots.insert(base.join("libraries"));
+    for (folder, profile, index) in &profiles {
+        referenced.extend(profile_files(base, folder, profile)?);
+        referenced.extend(library_files(profile, index.as_ref()));
+        let assets = base.join(&profile.assets_dir);
+        roots.insert(assets.join("objects"));
+        roots.insert(assets.join("indexes"));
+    }
+    let mut files = Vec::new();
+    for root in roots {
+        for entry in WalkDir::new(&root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+        {
+            let path = relative(base, entry.path());
+            if !referenced.contains(&path) {
+                files.push((root.clone(), path));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Removes files found by [`unreferenced_files`] and the folders they leave empty.
+pub fn remove(base: &Path, files: &[(PathBuf, String)]) -> usize {
+    let mut removed = 0;
+    for (root, path) in files {
+        let file = base.join(path);
+        if let Err(err) = remove_file(&file) {
+            log::warn!("Can't remove {}: {}", path, err);
+            continue;
+        }
+        remove_empty_parents(root, &file);
+        removed += 1;
+    }
+    removed
+}
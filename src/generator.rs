@@ -1,292 +1,514 @@
-use crate::download::{download_file, download_files_concurrent, download_files_single};
+use crate::artifact::Artifact;
+use crate::download::DownloadPool;
+use crate::error::{GeneratorError, Result};
+use crate::index::{write_index, PROFILE_FILE};
+use crate::library_set::{LibrarySet, Priority};
+use crate::lock::{GenerationInputs, Lockfile};
+use crate::maven::pom::resolve_dependencies;
+use crate::maven::{Resolver, FABRIC};
 use crate::minecraft::forge::LibraryType;
+use crate::minecraft::libraries::Library;
+use crate::minecraft::multimc::{apply_components, resolve_components};
+use crate::minecraft::optifine::{
+    LAUNCHWRAPPER, LAUNCHWRAPPER_MAIN_CLASS, LAUNCHWRAPPER_URL, TWEAKER,
+};
+use crate::minecraft::runtime::{install_runtime, plan_runtimes, RuntimeLocation, RUNTIME_FILE};
+use crate::minecraft::tweaker::{TweakerChain, FORGE_ORDER, LITELOADER_ORDER, OPTIFINE_ORDER};
 use crate::minecraft::version::Libraries;
-use crate::minecraft::GameType;
 use crate::minecraft::GameType::{Fabric, Forge};
-use crate::util::{generate_download_url, generate_lib_path, get_yarn_path, get_yarn_url, jar_url};
-use anyhow::Result;
+use crate::minecraft::{Addons, GameType};
+use crate::plan::{Plan, PlannedFile};
+use crate::util::{asset_url, library_key, write_json_file};
 use launcher_api::profile::Profile;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::{create_dir_all, remove_dir_all, File};
 use std::io;
-use std::iter::FromIterator;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 use zip::ZipArchive;
 
+pub const DEFAULT_UPDATE_VERIFY: [&str; 4] = ["libraries", "natives", "minecraft.jar", "mods"];
+pub const DEFAULT_UPDATE_EXCLUSION: [&str; 3] = ["options.txt", "saves", "screenshots"];
+
+/// Profile settings that don't come from game or loader metadata.
+pub struct Settings {
+    /// Folder with libraries, assets and natives, the profile goes to its `<name>` subfolder.
+    pub output: PathBuf,
+    pub address: String,
+    pub port: u32,
+    pub resolver: Resolver,
+    pub pool: DownloadPool,
+    /// Libraries added to the profile together with their runtime dependencies.
+    pub extra_libraries: Vec<Artifact>,
+    /// Files and folders the launcher checks against the server before start.
+    pub update_verify: Vec<String>,
+    /// Files and folders the launcher never overwrites.
+    pub update_exclusion: Vec<String>,
+    /// JVM arguments added after the ones the loader requires.
+    pub jvm_args: Vec<String>,
+    /// Mod jars copied to the profile `mods` folder.
+    pub mods: Vec<PathBuf>,
+    /// Platforms to bundle a Java runtime for, e.g. `linux` or `windows-x64`.
+    pub runtime_platforms: Vec<String>,
+    /// Folder with `<component>-<platform>.zip` runtime archives used instead of Mojang downloads.
+    pub runtime_archives: Option<PathBuf>,
+    /// Only resolve the plan, nothing is downloaded or written.
+    pub dry_run: bool,
+    /// Lock the resolved plan must match.
+    pub locked: Option<Lockfile>,
+    /// Inputs recorded in the lockfile.
+    pub inputs: GenerationInputs,
+}
+
+/// Resolves the profile and, unless `settings.dry_run` is set, installs it to `settings.output`.
 pub fn generate_profile(
     name: &str,
     version: &str,
     manifest: Libraries,
-    address: &str,
-    port: u32,
     game_type: GameType,
-) -> Result<()> {
-    let base = PathBuf::from(name);
-    let native_folder = &base.join("natives").join(version);
-    let assets_folder = &base.join("assets");
-    let profile_folder = &base.join(name);
-    let libraries_folder = base.join("libraries");
+    addons: Addons,
+    settings: &Settings,
+) -> Result<Plan> {
+    let mut plan = plan_profile(name, version, manifest, game_type, &addons, settings)?;
+    if let Some(lock) = &settings.locked {
+        lock.apply(&mut plan)?;
+    }
+    if !settings.dry_run {
+        install(&plan, addons, settings)?;
+    }
+    Ok(plan)
+}
+
+/// Resolves game, loader and library metadata without writing to the output folder.
+pub fn plan_profile(
+    name: &str,
+    version: &str,
+    mut manifest: Libraries,
+    game_type: GameType,
+    addons: &Addons,
+    settings: &Settings,
+) -> Result<Plan> {
+    let resolver = &settings.resolver;
     let mut client_args = Vec::new();
+    let mut jvm_args = Vec::new();
+    let mut tweakers = TweakerChain::default();
     let mut main_class = "net/minecraft/client/main/Main".to_string();
-    let mut classpath = Vec::new();
-    std::fs::create_dir_all(&native_folder)?;
-    std::fs::create_dir_all(&assets_folder)?;
-    std::fs::create_dir_all(&profile_folder)?;
-    std::fs::create_dir_all(&libraries_folder)?;
-    log::info!("Download assets...");
-    let assets = crate::util::get_assets(&manifest.asset_index.url).unwrap();
-    let objects_path = assets_folder.join("objects");
-    let mut assets_download = Vec::new();
-    for (_, object) in assets.objects {
-        let path = objects_path.join(&object.hash[0..2]);
-        assets_download.push((
-            format!(
-                "http://resources.download.minecraft.net/{}/{}",
-                &object.hash[0..2],
-                object.hash
-            ),
-            path.to_str().unwrap().to_string(),
-        ));
-    }
-    download_files_single(&assets_download);
-    download_file(
-        &manifest.asset_index.url,
-        assets_folder.join("indexes").to_str().unwrap(),
-    );
-    log::info!("Download client...");
-    download_file(
-        &manifest.downloads.client.unwrap().url,
-        &profile_folder.to_str().unwrap(),
-    );
-    std::fs::rename(
-        profile_folder.join("client.jar").as_path(),
-        profile_folder.join("minecraft.jar").as_path(),
-    )?;
-    classpath.push("minecraft.jar".to_string());
-    log::info!("Download libs...");
-    let mut profile_lib_paths = HashSet::new();
-    let libs: Vec<(String, String)> = manifest
-        .libraries
-        .iter()
-        .filter(|v| v.downloads.artifact.is_some())
-        .map(|v| {
-            let mut lib_path = PathBuf::from(
-                v.downloads
-                    .artifact
-                    .as_ref()
-                    .unwrap()
-                    .path
-                    .as_ref()
-                    .unwrap()
-                    .to_string(),
-            );
-            profile_lib_paths.insert(lib_path.to_str().unwrap().to_string());
-            lib_path.pop();
-            let url = v.downloads.artifact.as_ref().unwrap().url.to_string();
-            let path = libraries_folder.join(lib_path);
-            (url, path.to_str().unwrap().to_string())
+    let classpath = vec!["minecraft.jar".to_string()];
+    // every library file seen, only the ones `profile_lib_paths` keeps are planned
+    let mut candidates: Vec<PlannedFile> = Vec::new();
+    let mut maven_files = Vec::new();
+    log::info!("Resolve assets...");
+    let mut assets: Vec<PlannedFile> = crate::util::get_assets(&manifest.asset_index.url)?
+        .objects
+        .values()
+        .map(|object| PlannedFile {
+            url: asset_url(&object.hash),
+            path: format!("assets/objects/{}/{}", &object.hash[0..2], object.hash),
+            sha1: Some(object.hash.to_string()),
+            size: Some(object.size as u64),
         })
         .collect();
-    download_files_concurrent(&libs);
+    assets.sort_by(|a, b| a.path.cmp(&b.path));
+    let asset_index = PlannedFile {
+        url: manifest.asset_index.url.to_string(),
+        path: format!("assets/indexes/{}.json", manifest.asset_index.id),
+        sha1: Some(manifest.asset_index.sha1.to_string()),
+        size: None,
+    };
+    let client = client_file(&manifest, name)?;
+    if let Forge(forge_manifest) = &game_type {
+        if let Some(requires) = &forge_manifest.requires {
+            log::info!("Resolve forge components...");
+            let components = resolve_components(requires)?;
+            apply_components(&mut manifest.libraries, components);
+        }
+    }
+    log::info!("Resolve libs...");
+    let mut profile_lib_paths = LibrarySet::default();
+    for library in &manifest.libraries {
+        if let Some(file) = &library.downloads.artifact {
+            if let Some(path) = &file.path {
+                profile_lib_paths.insert(&library.name, path.to_string(), Priority::Default);
+                candidates.push(PlannedFile::from_library(file, path));
+            }
+        }
+    }
+    let is_forge = matches!(game_type, Forge(_));
+    let loader_libraries: HashSet<String> = match &game_type {
+        Forge(forge_manifest) => forge_manifest
+            .libraries
+            .iter()
+            .map(|v| library_key(v.name()).to_string())
+            .collect(),
+        _ => HashSet::new(),
+    };
     match game_type {
-        Fabric(mut fabric_manifest) => {
-            fabric_manifest
-                .libraries
-                .client
-                .append(&mut fabric_manifest.libraries.common);
-            let fabric_libs: Vec<(String, String)> = fabric_manifest
+        Fabric(fabric_manifest) => {
+            let mut requests: Vec<(String, String)> = fabric_manifest
                 .libraries
                 .client
                 .iter()
-                .map(|v| {
-                    let mut lib_path = PathBuf::from(generate_lib_path(&v.name));
-                    profile_lib_paths.insert(lib_path.to_str().unwrap().to_string());
-                    lib_path.pop();
-                    let url = generate_download_url(&v.url, &v.name);
-                    let path = libraries_folder.join(lib_path);
-                    (url, path.to_str().unwrap().to_string())
-                })
+                .chain(&fabric_manifest.libraries.common)
+                .map(|v| (v.url.to_string(), v.name.to_string()))
                 .collect();
-            download_files_concurrent(&fabric_libs);
-            let mappings_url = get_yarn_url(version);
-            let mut lib_path = PathBuf::from(get_yarn_path(&version));
-            profile_lib_paths.insert(lib_path.to_str().unwrap().to_string());
-            lib_path.pop();
-            let path = libraries_folder.join(lib_path);
-            download_file(&mappings_url, path.to_str().unwrap());
+            requests.push((
+                FABRIC.to_string(),
+                format!("net.fabricmc:intermediary:{}", version),
+            ));
+            for ((_, name), (url, path)) in requests.iter().zip(resolver.resolve_all(&requests)?) {
+                profile_lib_paths.insert(name, path.to_string(), Priority::Loader);
+                candidates.push(PlannedFile::library(url, &path));
+            }
             main_class = fabric_manifest.main_class.client;
         }
         Forge(forge_manifest) => {
-            main_class = forge_manifest.main_class;
-            let libs: Vec<(String, String)> = forge_manifest
-                .libraries
-                .iter()
-                .filter_map(|v| match v {
-                    LibraryType::PathLibrary(v) => Some(v),
-                    _ => None,
-                })
-                .filter(|v| v.downloads.artifact.is_some())
-                .filter_map(|v| {
-                    // TODO: Add forge installer support
-                    if v.downloads.artifact.as_ref().unwrap().path.is_some() {
-                        let mut lib_path = PathBuf::from(
-                            v.downloads
-                                .artifact
-                                .as_ref()
-                                .unwrap()
-                                .path
-                                .as_ref()
-                                .unwrap()
-                                .to_string(),
-                        );
-                        profile_lib_paths.insert(lib_path.to_str().unwrap().to_string());
-                        lib_path.pop();
-                        let url = v.downloads.artifact.as_ref().unwrap().url.to_string();
-                        let path = libraries_folder.join(lib_path);
-                        return Some((url, path.to_str().unwrap().to_string()));
+            main_class = forge_manifest.main_class.clone();
+            // TODO: Add forge installer support
+            for library in forge_manifest.libraries.iter().filter_map(|v| match v {
+                LibraryType::PathLibrary(v) => Some(v),
+                _ => None,
+            }) {
+                if let Some(file) = &library.downloads.artifact {
+                    if let Some(path) = &file.path {
+                        profile_lib_paths.insert(&library.name, path.to_string(), Priority::Loader);
+                        candidates.push(PlannedFile::from_library(file, path));
                     }
-                    return None;
-                })
-                .collect();
-            download_files_concurrent(&libs);
-            let libs: Vec<(String, String)> = forge_manifest
+                }
+            }
+            let requests: Vec<(String, String)> = forge_manifest
                 .libraries
                 .iter()
                 .filter_map(|v| match v {
-                    LibraryType::NameLibrary(v) => Some(v),
+                    LibraryType::NameLibrary(v) => Some((v.url.to_string(), v.name.to_string())),
                     _ => None,
                 })
-                .map(|v| {
-                    let mut lib_path = PathBuf::from(generate_lib_path(&v.name));
-                    profile_lib_paths.insert(lib_path.to_str().unwrap().to_string());
-                    lib_path.pop();
-                    let url = generate_download_url(&v.url, &v.name);
-                    let path = libraries_folder.join(lib_path);
-                    (url, path.to_str().unwrap().to_string())
-                })
                 .collect();
-            download_files_concurrent(&libs);
-            // list of maven files to put in the libraries folder, but not in classpath
-            if let Some(files) = forge_manifest.maven_files {
-                let libs: Vec<(String, String)> = files
-                    .iter()
-                    .filter_map(|v| {
-                        if v.downloads.artifact.as_ref().unwrap().path.is_some() {
-                            let mut lib_path = PathBuf::from(
-                                v.downloads
-                                    .artifact
-                                    .as_ref()
-                                    .unwrap()
-                                    .path
-                                    .as_ref()
-                                    .unwrap()
-                                    .to_string(),
-                            );
-                            lib_path.pop();
-                            let url = v.downloads.artifact.as_ref().unwrap().url.to_string();
-                            let path = libraries_folder.join(lib_path);
-                            return Some((url, path.to_str().unwrap().to_string()));
-                        }
-                        return None;
-                    })
-                    .collect();
-                download_files_concurrent(&libs);
+            for ((_, name), (url, path)) in requests.iter().zip(resolver.resolve_all(&requests)?) {
+                profile_lib_paths.insert(name, path.to_string(), Priority::Loader);
+                candidates.push(PlannedFile::library(url, &path));
             }
-            if let Some(tweakers) = forge_manifest.tweakers {
-                for tweak in tweakers {
-                    client_args.push("--tweakClass".to_string());
-                    client_args.push(tweak);
+            // list of maven files to put in the libraries folder, but not in classpath
+            if let Some(files) = &forge_manifest.maven_files {
+                for file in files.iter().filter_map(|v| v.downloads.artifact.as_ref()) {
+                    if let Some(path) = &file.path {
+                        maven_files.push(PlannedFile::from_library(file, path));
+                    }
                 }
             }
+            if let Some(forge_tweakers) = &forge_manifest.tweakers {
+                tweakers.add(forge_manifest.order.unwrap_or(FORGE_ORDER), forge_tweakers);
+            }
+            client_args.extend(forge_manifest.client_args());
+            if let Some(args) = forge_manifest.jvm_args {
+                jvm_args.extend(args);
+            }
+            if let Some(traits) = forge_manifest.traits {
+                log::info!("Forge traits: {}", traits.join(", "));
+            }
         }
         _ => {}
     }
-    log::info!("Download natives...");
-    let temp_natives = base.join("natives_temp");
-    create_dir_all(&temp_natives)?;
-    let natives = manifest
-        .libraries
-        .iter()
-        .filter(|v| v.downloads.classifiers.is_some())
-        .flat_map(|v| {
-            let mut natives: Vec<(String, String)> = Vec::new();
-            if let Some(f) = v
-                .downloads
-                .classifiers
-                .as_ref()
-                .unwrap()
-                .natives_osx
-                .as_ref()
-            {
-                natives.push(jar_url(&temp_natives, f));
+    if let Some(liteloader_manifest) = &addons.liteloader {
+        log::info!("Resolve liteloader libs...");
+        // libraries already provided by the loader are not downloaded twice
+        for library in liteloader_manifest
+            .libraries
+            .iter()
+            .filter(|v| !loader_libraries.contains(library_key(v.name())))
+        {
+            let (path, file) = match library {
+                LibraryType::PathLibrary(v) => match &v.downloads.artifact {
+                    Some(artifact) => match &artifact.path {
+                        Some(path) => (path.to_string(), PlannedFile::from_library(artifact, path)),
+                        None => continue,
+                    },
+                    None => continue,
+                },
+                LibraryType::NameLibrary(v) => {
+                    let (url, path) = resolver.resolve(&v.url, &v.name)?;
+                    let file = PlannedFile::library(url, &path);
+                    (path, file)
+                }
+            };
+            profile_lib_paths.insert(library.name(), path, Priority::Loader);
+            candidates.push(file);
+        }
+        if let Some(liteloader_tweakers) = &liteloader_manifest.tweakers {
+            let order = liteloader_manifest.order.unwrap_or(LITELOADER_ORDER);
+            tweakers.add(order, liteloader_tweakers);
+        }
+        if !is_forge {
+            if let Some(liteloader_main_class) = &liteloader_manifest.main_class {
+                main_class = liteloader_main_class.to_string();
             }
-            if let Some(f) = v
-                .downloads
-                .classifiers
-                .as_ref()
-                .unwrap()
-                .natives_windows
-                .as_ref()
-            {
-                natives.push(jar_url(&temp_natives, f));
+        }
+    }
+    if let Some(optifine) = &addons.optifine {
+        // Forge loads OptiFine as a mod, otherwise the patched client goes to libraries
+        if !is_forge {
+            for (lib_name, lib_path) in optifine.libraries()? {
+                if lib_name == LAUNCHWRAPPER {
+                    candidates.push(PlannedFile::library(
+                        LAUNCHWRAPPER_URL.to_string(),
+                        &lib_path,
+                    ));
+                }
+                profile_lib_paths.insert(&lib_name, lib_path, Priority::Loader);
             }
-            if let Some(f) = v
-                .downloads
-                .classifiers
-                .as_ref()
-                .unwrap()
-                .natives_linux
-                .as_ref()
-            {
-                natives.push(jar_url(&temp_natives, f));
+            main_class = LAUNCHWRAPPER_MAIN_CLASS.to_string();
+            tweakers.add(OPTIFINE_ORDER, &[TWEAKER.to_string()]);
+        }
+    }
+    if !settings.extra_libraries.is_empty() {
+        log::info!("Resolve extra libs...");
+        for artifact in resolve_dependencies(resolver, &settings.extra_libraries)? {
+            let (url, path) = resolver.locate(&artifact)?;
+            profile_lib_paths.insert(&artifact.to_string(), path.to_string(), Priority::Default);
+            candidates.push(PlannedFile::library(url, &path));
+        }
+    }
+    if !tweakers.is_empty() {
+        let mut args = tweakers.into_args();
+        args.append(&mut client_args);
+        client_args = args;
+    }
+    let logging = manifest
+        .logging
+        .as_ref()
+        .and_then(|v| v.client.as_ref())
+        .map(|config| {
+            // the launcher starts the client in the profile folder, a configuration file
+            // from the settings comes later in the arguments and overrides this one
+            jvm_args.push(config.argument.replace("${path}", &config.file.id));
+            PlannedFile {
+                url: config.file.url.to_string(),
+                path: format!("{}/{}", name, config.file.id),
+                sha1: Some(config.file.sha1.to_string()),
+                size: Some(config.file.size),
             }
-            natives
+        });
+    for arg in &settings.jvm_args {
+        if !jvm_args.contains(arg) {
+            jvm_args.push(arg.to_string());
+        }
+    }
+    let natives = native_files(&manifest.libraries, version);
+    let mods = settings
+        .mods
+        .iter()
+        .map(|v| {
+            let file_name = v
+                .file_name()
+                .ok_or_else(|| GeneratorError::config(v.display(), "not a mod file"))?;
+            Ok(PlannedFile {
+                url: v.to_string_lossy().into_owned(),
+                path: format!("{}/mods/{}", name, file_name.to_string_lossy()),
+                sha1: None,
+                size: v.metadata().ok().map(|v| v.len()),
+            })
         })
-        .collect::<Vec<(String, String)>>();
-    download_files_concurrent(&natives);
-    for entry in WalkDir::new(&temp_natives)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().is_file())
-    {
-        if let Ok(mut zip) = ZipArchive::new(File::open(entry.path()).unwrap()) {
-            for index in 0..zip.len() {
-                let mut file = zip.by_index(index).unwrap();
-                if file.is_file() {
-                    if file.name().ends_with(".so")
-                        || file.name().ends_with(".dll")
-                        || file.name().ends_with(".dylib")
-                    {
-                        if let Ok(mut outfile) =
-                            File::create(native_folder.join(&file.mangled_name()))
-                        {
-                            io::copy(&mut file, &mut outfile)?;
-                        }
-                    }
-                }
-            }
+        .collect::<Result<_>>()?;
+    let runtimes = match &manifest.java_version {
+        Some(java) if !settings.runtime_platforms.is_empty() => {
+            log::info!("Resolve Java {} runtime...", java.major_version);
+            plan_runtimes(
+                java,
+                &settings.runtime_platforms,
+                settings.runtime_archives.as_deref(),
+            )?
         }
+        _ => Vec::new(),
+    };
+    let lib_paths = profile_lib_paths.paths();
+    let mut by_path: HashMap<String, PlannedFile> = HashMap::new();
+    for file in candidates {
+        // the first entry of a path is kept, vanilla ones come with hashes
+        by_path.entry(file.path.clone()).or_insert(file);
     }
-    remove_dir_all(temp_natives)?;
-    log::info!("Generate json profile...");
-    serde_json::to_writer_pretty(
-        File::create(profile_folder.join(format!("profile.json"))).unwrap(),
-        &Profile {
+    // one file per path, OptiFine's patched client has none as it is built on install
+    let libraries = lib_paths
+        .iter()
+        .filter_map(|path| by_path.remove(path))
+        .collect();
+    Ok(Plan {
+        version_sha1: manifest.sha1,
+        profile: Profile {
             name: name.to_string(),
             version: version.to_string(),
-            libraries: Vec::from_iter(profile_lib_paths),
+            libraries: lib_paths,
             class_path: classpath,
             main_class,
-            update_verify: vec![],
-            update_exclusion: vec![],
-            jvm_args: vec![],
+            update_verify: settings.update_verify.clone(),
+            update_exclusion: settings.update_exclusion.clone(),
+            jvm_args,
             client_args,
             assets: manifest.asset_index.id,
             assets_dir: "assets".to_string(),
-            server_name: address.to_string(),
-            server_port: port,
+            server_name: settings.address.to_string(),
+            server_port: settings.port,
         },
-    )?;
+        client,
+        asset_index,
+        assets,
+        libraries,
+        maven_files,
+        natives,
+        mods,
+        logging,
+        runtimes,
+        addons: addon_names(addons),
+    })
+}
+
+fn addon_names(addons: &Addons) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some(liteloader) = &addons.liteloader {
+        names.push(format!("liteloader {}", liteloader.version));
+    }
+    if let Some(optifine) = &addons.optifine {
+        names.push(format!("optifine {}", optifine.version));
+    }
+    names
+}
+
+/// Downloads planned files, installs addons and writes `profile.json` and the file index.
+/// Every download is checked on arrival, so a broken file stops the install
+/// before the profile, its index or its lockfile are written.
+fn install(plan: &Plan, addons: Addons, settings: &Settings) -> Result<()> {
+    let base = settings.output.as_path();
+    let profile = &plan.profile;
+    let native_folder = &base.join("natives").join(&profile.version);
+    let profile_folder = &base.join(&profile.name);
+    let libraries_folder = base.join("libraries");
+    log::info!("Download assets...");
+    let pool = &settings.pool;
+    pool.download_single(base, &plan.assets)?;
+    pool.download(base, &plan.asset_index)?;
+    log::info!("Download libs...");
+    pool.download_all(base, &plan.libraries)?;
+    pool.download_all(base, &plan.maven_files)?;
+    log::info!("Download natives...");
+    create_dir_all(native_folder).map_err(|e| GeneratorError::io(native_folder, e))?;
+    download_natives(pool, &plan.natives, base, native_folder)?;
+    let mut written_files: Vec<String> = plan.maven_files.iter().map(|v| v.path.clone()).collect();
+    for runtime in &plan.runtimes {
+        log::info!(
+            "Install {} runtime for {}...",
+            runtime.component,
+            runtime.platform
+        );
+        written_files.extend(install_runtime(pool, runtime, base)?);
+    }
+    log::info!("Download client...");
+    pool.download(base, &plan.client)?;
+    if let Some(logging) = &plan.logging {
+        log::info!("Download logging configuration...");
+        pool.download(base, logging)?;
+    }
+    if let Some(optifine) = addons.optifine {
+        log::info!("Install OptiFine {}...", optifine.version);
+        if profile.libraries.contains(&optifine.library_path()) {
+            optifine.install_library(&profile_folder.join("minecraft.jar"), &libraries_folder)?;
+        } else {
+            optifine.install_mod(&profile_folder.join("mods"))?;
+        }
+    }
+    if !plan.mods.is_empty() {
+        log::info!("Copy mods...");
+        pool.download_single(base, &plan.mods)?;
+    }
+    if !plan.runtimes.is_empty() {
+        let locations: Vec<RuntimeLocation> = plan.runtimes.iter().map(|v| v.location()).collect();
+        write_json_file(&profile_folder.join(RUNTIME_FILE), &locations)?;
+    }
+    log::info!("Generate json profile...");
+    write_json_file(&profile_folder.join(PROFILE_FILE), profile)?;
+    log::info!("Generate file index...");
+    let index = write_index(base, profile_folder, &written_files)?;
+    Lockfile::new(plan, &index, &settings.inputs).write(profile_folder)?;
     Ok(())
 }
+
+pub fn client_file(manifest: &Libraries, name: &str) -> Result<PlannedFile> {
+    let client = manifest.downloads.client.as_ref().ok_or_else(|| {
+        GeneratorError::metadata(format!("version of {}", name), "no client download")
+    })?;
+    Ok(PlannedFile {
+        url: client.url.to_string(),
+        path: format!("{}/minecraft.jar", name),
+        sha1: Some(client.sha1.to_string()),
+        size: client.size,
+    })
+}
+
+/// Native jars of the version, they are extracted to `natives/<version>`.
+pub fn native_files(libraries: &[Library], version: &str) -> Vec<PlannedFile> {
+    libraries
+        .iter()
+        .filter_map(|v| v.downloads.classifiers.as_ref())
+        .flat_map(|v| {
+            vec![&v.natives_osx, &v.natives_windows, &v.natives_linux]
+                .into_iter()
+                .flatten()
+        })
+        .map(|v| PlannedFile {
+            url: v.url.to_string(),
+            path: format!("natives/{}", version),
+            sha1: Some(v.sha1.to_string()),
+            size: v.size,
+        })
+        .collect()
+}
+
+/// Downloads native libraries and extracts `.so`, `.dll` and `.dylib` files to `native_folder`.
+pub fn download_natives(
+    pool: &DownloadPool,
+    natives: &[PlannedFile],
+    base: &Path,
+    native_folder: &Path,
+) -> Result<()> {
+    let temp_natives = base.join("natives_temp");
+    create_dir_all(&temp_natives).map_err(|e| GeneratorError::io(&temp_natives, e))?;
+    // every native jar of the version shares the planned folder, so each gets its own temporary name
+    let natives: Vec<PlannedFile> = natives
+        .iter()
+        .map(|v| PlannedFile {
+            path: format!(
+                "natives_temp/{}",
+                v.url.rsplit('/').next().unwrap_or(&v.url)
+            ),
+            ..v.clone()
+        })
+        .collect();
+    pool.download_all(base, &natives)?;
+    for entry in WalkDir::new(&temp_natives)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+    {
+        let jar = File::open(entry.path()).map_err(|e| GeneratorError::io(entry.path(), e))?;
+        let mut zip =
+            ZipArchive::new(jar).map_err(|e| GeneratorError::io(entry.path(), e.into()))?;
+        for index in 0..zip.len() {
+            let mut file = zip
+                .by_index(index)
+                .map_err(|e| GeneratorError::io(entry.path(), e.into()))?;
+            if file.is_file() {
+                if file.name().ends_with(".so")
+                    || file.name().ends_with(".dll")
+                    || file.name().ends_with(".dylib")
+                {
+                    let output = native_folder.join(file.mangled_name());
+                    if let Some(parent) = output.parent() {
+                        create_dir_all(parent).map_err(|e| GeneratorError::io(parent, e))?;
+                    }
+                    let mut outfile =
+                        File::create(&output).map_err(|e| GeneratorError::io(&output, e))?;
+                    io::copy(&mut file, &mut outfile)
+                        .map_err(|e| GeneratorError::io(entry.path(), e))?;
+                }
+            }
+        }
+    }
+    remove_dir_all(&temp_natives).map_err(|e| GeneratorError::io(&temp_natives, e))
+}
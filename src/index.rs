@@ -0,0 +1,169 @@
+use crate::error::{GeneratorError, Result};
+use crate::lock::LOCK_FILE;
+use crate::minecraft::assets::Assets;
+use crate::util::{read_json_file, write_json_file};
+use launcher_api::profile::Profile;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs::{read_dir, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+pub const PROFILE_FILE: &str = "profile.json";
+pub const INDEX_FILE: &str = "index.json";
+
+/// Expected size and hashes of every file of a profile, paths are relative to the
+/// folder that holds `libraries`, `assets` and `natives`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct FileIndex {
+    pub files: Vec<IndexedFile>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IndexedFile {
+    pub path: String,
+    pub size: u64,
+    pub sha1: String,
+    pub sha256: String,
+}
+
+impl IndexedFile {
+    pub fn read(base: &Path, path: &str) -> Result<Self> {
+        let full_path = base.join(path);
+        let mut file = File::open(&full_path).map_err(|e| GeneratorError::io(&full_path, e))?;
+        let mut sha1 = Sha1::new();
+        let mut sha256 = Sha256::new();
+        let mut size = 0;
+        let mut buffer = [0; 64 * 1024];
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .map_err(|e| GeneratorError::io(&full_path, e))?;
+            if read == 0 {
+                break;
+            }
+            sha1.update(&buffer[..read]);
+            sha256.update(&buffer[..read]);
+            size += read as u64;
+        }
+        Ok(IndexedFile {
+            path: path.to_string(),
+            size,
+            sha1: hex::encode(sha1.finalize()),
+            sha256: hex::encode(sha256.finalize()),
+        })
+    }
+}
+
+pub fn read_profile(profile_folder: &Path) -> Result<Profile> {
+    read_json_file(&profile_folder.join(PROFILE_FILE))
+}
+
+pub fn read_index(profile_folder: &Path) -> Result<FileIndex> {
+    read_json_file(&profile_folder.join(INDEX_FILE))
+}
+
+/// Files referenced by a profile: the profile folder itself without `update_exclusion`,
+/// its libraries, natives and assets.
+pub fn profile_files(
+    base: &Path,
+    profile_folder: &Path,
+    profile: &Profile,
+) -> Result<BTreeSet<String>> {
+    let mut files = BTreeSet::new();
+    // the index and lockfile of the profile describe the other files, they aren't part of them
+    let own_files = [
+        relative(base, &profile_folder.join(INDEX_FILE)),
+        relative(base, &profile_folder.join(LOCK_FILE)),
+    ];
+    // user files such as options.txt or saves are never written by the generator
+    let excluded: Vec<PathBuf> = profile
+        .update_exclusion
+        .iter()
+        .map(|v| profile_folder.join(v))
+        .collect();
+    for folder in &[
+        profile_folder.to_path_buf(),
+        base.join("natives").join(&profile.version),
+    ] {
+        for entry in WalkDir::new(folder)
+            .into_iter()
+            .filter_entry(|e| !excluded.iter().any(|v| e.path() == v))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+        {
+            let path = relative(base, entry.path());
+            if !own_files.contains(&path) {
+                files.insert(path);
+            }
+        }
+    }
+    for library in &profile.libraries {
+        files.insert(format!("libraries/{}", library));
+    }
+    let asset_index = format!("{}/indexes/{}.json", profile.assets_dir, profile.assets);
+    if base.join(&asset_index).is_file() {
+        let assets: Assets = read_json_file(&base.join(&asset_index))?;
+        for object in assets.objects.values() {
+            files.insert(format!(
+                "{}/objects/{}/{}",
+                profile.assets_dir,
+                &object.hash[0..2],
+                object.hash
+            ));
+        }
+    }
+    files.insert(asset_index);
+    Ok(files)
+}
+
+/// Hashes profile files and writes `index.json` next to `profile.json`.
+/// `extra` lists files that `profile.json` doesn't reference, e.g. Forge maven files.
+pub fn write_index(base: &Path, profile_folder: &Path, extra: &[String]) -> Result<FileIndex> {
+    let profile = read_profile(profile_folder)?;
+    let mut files = profile_files(base, profile_folder, &profile)?;
+    files.extend(extra.iter().cloned());
+    let mut index = FileIndex::default();
+    for path in files {
+        match IndexedFile::read(base, &path) {
+            Ok(file) => index.files.push(file),
+            Err(err) => log::warn!("Can't index {}: {}", path, err),
+        }
+    }
+    write_json_file(&profile_folder.join(INDEX_FILE), &index)?;
+    Ok(index)
+}
+
+/// Profiles stored in subfolders of `base` with their index, if they have one.
+/// A profile or index that can't be read is an error,
+/// callers that delete files must not miss what it references.
+pub fn read_profiles_in(base: &Path) -> Result<Vec<(PathBuf, Profile, Option<FileIndex>)>> {
+    let mut profiles = Vec::new();
+    for entry in read_dir(base).map_err(|e| GeneratorError::io(base, e))? {
+        let folder = entry.map_err(|e| GeneratorError::io(base, e))?.path();
+        if !folder.join(PROFILE_FILE).is_file() {
+            continue;
+        }
+        let profile = read_profile(&folder)?;
+        let index = if folder.join(INDEX_FILE).is_file() {
+            Some(read_index(&folder)?)
+        } else {
+            None
+        };
+        profiles.push((folder, profile, index));
+    }
+    profiles.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(profiles)
+}
+
+pub fn relative(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .map(|v| v.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,26 @@
+//! Generates NSLauncher profiles: resolves game, loader and library metadata,
+//! downloads files and writes `profile.json`.
+
+pub mod artifact;
+pub mod builder;
+pub mod cache;
+pub mod config;
+pub mod download;
+pub mod error;
+pub mod gc;
+pub mod generator;
+pub mod index;
+mod library_set;
+pub mod lock;
+pub mod maven;
+pub mod minecraft;
+pub mod plan;
+pub mod update;
+mod util;
+pub mod verify;
+pub mod workspace;
+
+pub use crate::artifact::Artifact;
+pub use crate::builder::{Loader, ProfileGenerator};
+pub use crate::error::{GeneratorError, Result};
+pub use crate::plan::Plan;
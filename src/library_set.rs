@@ -0,0 +1,77 @@
+use crate::artifact::{compare_versions, Artifact};
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Default,
+    /// Versions requested by the mod loader replace the vanilla ones.
+    Loader,
+}
+
+struct Entry {
+    key: String,
+    name: String,
+    version: String,
+    path: String,
+    priority: Priority,
+}
+
+/// Libraries of a profile with one version per group, artifact and classifier.
+/// On conflict the loader version wins, otherwise the highest version is kept.
+/// Libraries keep the order they were declared in, loader libraries go first.
+#[derive(Default)]
+pub struct LibrarySet {
+    entries: Vec<Entry>,
+}
+
+impl LibrarySet {
+    pub fn insert(&mut self, name: &str, path: String, priority: Priority) {
+        let (key, version) = match name.parse::<Artifact>() {
+            Ok(artifact) => (
+                format!(
+                    "{}:{}:{}",
+                    artifact.group,
+                    artifact.artifact,
+                    artifact.classifier.as_deref().unwrap_or_default()
+                ),
+                artifact.version,
+            ),
+            Err(err) => {
+                log::warn!("{}, library is added without conflict check", err);
+                (path.clone(), String::new())
+            }
+        };
+        if let Some(existing) = self.entries.iter().find(|v| v.key == key) {
+            if existing.path == path {
+                return;
+            }
+            let replace = match priority.cmp(&existing.priority) {
+                Ordering::Greater => true,
+                Ordering::Less => false,
+                Ordering::Equal => {
+                    compare_versions(&version, &existing.version) == Ordering::Greater
+                }
+            };
+            if !replace {
+                log::info!("Keep library {} instead of {}", existing.name, name);
+                return;
+            }
+            log::info!("Replace library {} with {}", existing.name, name);
+            self.entries.retain(|v| v.key != key);
+        }
+        self.entries.push(Entry {
+            key,
+            name: name.to_string(),
+            version,
+            path,
+            priority,
+        });
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        let mut entries: Vec<&Entry> = self.entries.iter().collect();
+        // stable sort keeps the declaration order inside a priority
+        entries.sort_by_key(|v| std::cmp::Reverse(v.priority));
+        entries.iter().map(|v| v.path.clone()).collect()
+    }
+}
@@ -0,0 +1,228 @@
+use crate::builder::Loader;
+use crate::download::file_sha1;
+use crate::error::{GeneratorError, Result};
+use crate::index::FileIndex;
+use crate::maven::is_remote;
+use crate::plan::{Plan, PlannedFile};
+use crate::update::detect_loader;
+use crate::util::{read_json_file, write_json_file};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub const LOCK_FILE: &str = "profile.lock";
+
+/// Resolved versions and hashes of a generated profile, written next to `profile.json`.
+#[derive(Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: String,
+    /// SHA-1 of the version JSON.
+    pub version_sha1: String,
+    pub loader: Option<String>,
+    /// LiteLoader and OptiFine versions.
+    #[serde(default)]
+    pub addons: Vec<String>,
+    pub asset_index: String,
+    pub asset_index_sha1: Option<String>,
+    /// Every planned file: client, assets, libraries, natives, mods, logging config and runtimes.
+    #[serde(alias = "libraries")]
+    pub files: Vec<LockedFile>,
+    /// Builder inputs that aren't in `profile.json`, restored by `update`.
+    #[serde(default)]
+    pub inputs: GenerationInputs,
+}
+
+/// Generation inputs of a profile besides its version, loader and server.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct GenerationInputs {
+    pub liteloader: Option<String>,
+    /// OptiFine installer jar.
+    pub optifine: Option<PathBuf>,
+    pub repositories: Vec<String>,
+    /// Extra library coordinates, e.g. `org.slf4j:slf4j-api:1.7.30`.
+    pub extra_libraries: Vec<String>,
+    /// JVM arguments given by the user, generated ones aren't included.
+    pub jvm_args: Vec<String>,
+    pub mods: Vec<PathBuf>,
+    pub runtime_platforms: Vec<String>,
+    pub runtime_archives: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LockedFile {
+    pub url: String,
+    pub path: String,
+    pub sha1: Option<String>,
+}
+
+impl Lockfile {
+    /// Lock of a plan, hashes the plan doesn't know are taken from `index` or the local source file.
+    pub fn new(plan: &Plan, index: &FileIndex, inputs: &GenerationInputs) -> Self {
+        let hashes: HashMap<&str, &str> = index
+            .files
+            .iter()
+            .map(|v| (v.path.as_str(), v.sha1.as_str()))
+            .collect();
+        Lockfile {
+            version: plan.profile.version.to_string(),
+            version_sha1: plan.version_sha1.to_string(),
+            loader: loader_name(plan),
+            addons: plan.addons.clone(),
+            asset_index: plan.profile.assets.to_string(),
+            asset_index_sha1: plan.asset_index.sha1.clone(),
+            files: plan
+                .files()
+                .into_iter()
+                .map(|v| {
+                    let sha1 = v
+                        .sha1
+                        .clone()
+                        .or_else(|| hashes.get(v.path.as_str()).map(|v| v.to_string()))
+                        .or_else(|| source_sha1(&v).ok().flatten());
+                    LockedFile {
+                        url: v.url,
+                        path: v.path,
+                        sha1,
+                    }
+                })
+                .collect(),
+            inputs: inputs.clone(),
+        }
+    }
+
+    pub fn read(profile_folder: &Path) -> Result<Self> {
+        read_json_file(&profile_folder.join(LOCK_FILE))
+    }
+
+    pub fn write(&self, profile_folder: &Path) -> Result<()> {
+        write_json_file(&profile_folder.join(LOCK_FILE), self)
+    }
+
+    /// Fails with `LockMismatch` if the plan resolved other versions or files than the lock
+    /// and with `LockDrift` if a locked file or metadata changed its hash.
+    /// Hashes missing from the plan are taken from the lock.
+    pub fn apply(&self, plan: &mut Plan) -> Result<()> {
+        if plan.profile.version != self.version {
+            return Err(GeneratorError::LockMismatch(format!(
+                "Version {} doesn't match the locked version {}",
+                plan.profile.version, self.version
+            )));
+        }
+        if plan.version_sha1 != self.version_sha1 {
+            return Err(GeneratorError::LockDrift {
+                source: format!("Version {}", self.version),
+                locked: self.version_sha1.to_string(),
+                actual: plan.version_sha1.to_string(),
+            });
+        }
+        let loader = loader_name(plan);
+        if loader != self.loader {
+            return Err(GeneratorError::LockMismatch(format!(
+                "Loader {} doesn't match the locked loader {}",
+                loader.as_deref().unwrap_or("none"),
+                self.loader.as_deref().unwrap_or("none")
+            )));
+        }
+        if plan.addons != self.addons {
+            return Err(GeneratorError::LockMismatch(format!(
+                "Addons [{}] don't match the locked addons [{}]",
+                plan.addons.join(", "),
+                self.addons.join(", ")
+            )));
+        }
+        if plan.profile.assets != self.asset_index {
+            return Err(GeneratorError::LockMismatch(format!(
+                "Asset index {} doesn't match the locked index {}",
+                plan.profile.assets, self.asset_index
+            )));
+        }
+        if let (Some(actual), Some(locked)) = (&plan.asset_index.sha1, &self.asset_index_sha1) {
+            if actual != locked {
+                return Err(GeneratorError::LockDrift {
+                    source: format!("Asset index {}", self.asset_index),
+                    locked: locked.to_string(),
+                    actual: actual.to_string(),
+                });
+            }
+        }
+        let locked: HashMap<(&str, &str), usize> = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, v)| ((v.url.as_str(), v.path.as_str()), i))
+            .collect();
+        let mut used = HashSet::new();
+        let mut check = |file: &PlannedFile| -> Result<Option<String>> {
+            let key = (file.url.as_str(), file.path.as_str());
+            let i = *locked.get(&key).ok_or_else(|| {
+                GeneratorError::LockMismatch(format!("{} is not in the lockfile", file.url))
+            })?;
+            used.insert(i);
+            let sha1 = match &self.files[i].sha1 {
+                Some(sha1) => sha1,
+                None => return Ok(None),
+            };
+            let actual = match &file.sha1 {
+                Some(planned) => Some(planned.to_string()),
+                None => source_sha1(file)?,
+            };
+            match actual {
+                Some(actual) if !actual.eq_ignore_ascii_case(sha1) => {
+                    Err(GeneratorError::LockDrift {
+                        source: file.url.to_string(),
+                        locked: sha1.to_string(),
+                        actual,
+                    })
+                }
+                _ => Ok(Some(sha1.to_string())),
+            }
+        };
+        for file in plan.files_mut() {
+            if let Some(sha1) = check(file)? {
+                file.sha1.get_or_insert(sha1);
+            }
+        }
+        for runtime in &plan.runtimes {
+            for file in runtime.planned_files() {
+                check(&file)?;
+            }
+        }
+        for runtime in plan.runtimes.iter().filter(|v| v.archive.is_some()) {
+            let archive = runtime.archive.as_ref().unwrap().to_string_lossy();
+            check(&PlannedFile {
+                url: archive.into_owned(),
+                path: runtime.path.to_string(),
+                sha1: None,
+                size: None,
+            })?;
+        }
+        let unused = (0..self.files.len()).find(|i| !used.contains(i));
+        if let Some(file) = unused.map(|i| &self.files[i]) {
+            return Err(GeneratorError::LockMismatch(format!(
+                "Locked {} is not used anymore",
+                file.url
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// SHA-1 of a local source file such as a mod jar or runtime archive, `None` for remote files.
+fn source_sha1(file: &PlannedFile) -> Result<Option<String>> {
+    if is_remote(&file.url) {
+        return Ok(None);
+    }
+    let source = Path::new(file.url.trim_start_matches("file://"));
+    if !source.is_file() {
+        return Ok(None);
+    }
+    file_sha1(source).map(Some)
+}
+
+fn loader_name(plan: &Plan) -> Option<String> {
+    match detect_loader(&plan.profile) {
+        Loader::Vanilla => None,
+        Loader::Forge(version) => Some(format!("forge {}", version)),
+        Loader::Fabric(version) => Some(format!("fabric {}", version)),
+    }
+}
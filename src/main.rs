@@ -1,16 +1,17 @@
-mod artifact;
-mod download;
-mod generator;
-mod minecraft;
-mod util;
 mod validator;
 
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, ArgMatches};
+use std::path::{Path, PathBuf};
 
-use crate::minecraft::fabric::FabricLoaderManifest;
-use crate::minecraft::forge::ForgeManifest;
-use crate::minecraft::version::Libraries;
-use crate::minecraft::GameType;
+use profile_generator::config::ProfileConfig;
+use profile_generator::download::DownloadPool;
+use profile_generator::generator::{DEFAULT_UPDATE_EXCLUSION, DEFAULT_UPDATE_VERIFY};
+use profile_generator::lock::Lockfile;
+use profile_generator::maven::Resolver;
+use profile_generator::minecraft::runtime::DEFAULT_RUNTIME_PLATFORMS;
+use profile_generator::plan::Plan;
+use profile_generator::workspace::{ProfileChanges, Workspace};
+use profile_generator::{gc, index, update, verify, Artifact, Loader, ProfileGenerator};
 
 fn main() {
     env_logger::init();
@@ -18,10 +19,11 @@ fn main() {
         .version("1.0")
         .author("Team NS")
         .about("Generate profile for NSLauncher")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::new("version")
                 .short('v')
-                .required(true)
+                .required_unless_present("config")
                 .long("version")
                 .takes_value(true)
                 .about("Minecraft Version"),
@@ -29,7 +31,7 @@ fn main() {
         .arg(
             Arg::new("profileName")
                 .short('n')
-                .required(true)
+                .required_unless_present("config")
                 .long("name")
                 .takes_value(true)
                 .about("Profile name"),
@@ -37,7 +39,7 @@ fn main() {
         .arg(
             Arg::new("serverName")
                 .short('a')
-                .required(true)
+                .required_unless_present("config")
                 .long("address")
                 .takes_value(true)
                 .about("Server address"),
@@ -45,7 +47,7 @@ fn main() {
         .arg(
             Arg::new("serverPort")
                 .short('p')
-                .required(true)
+                .required_unless_present("config")
                 .long("port")
                 .takes_value(true)
                 .about("Server port"),
@@ -66,36 +68,510 @@ fn main() {
                 .conflicts_with("forge")
                 .validator(validator::correct_fabric_version),
         )
+        .arg(
+            Arg::new("liteloader")
+                .about("LiteLoader Version")
+                .long("liteloader")
+                .takes_value(true)
+                .conflicts_with("fabric")
+                .validator(validator::correct_liteloader_version),
+        )
+        .arg(
+            Arg::new("optifine")
+                .about("OptiFine installer jar")
+                .long("optifine")
+                .takes_value(true)
+                .conflicts_with("fabric"),
+        )
+        .arg(
+            Arg::new("repository")
+                .about("Maven repository to look up libraries in, in order of preference")
+                .long("repository")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("extraLibrary")
+                .about("Additional library group:artifact:version, runtime dependencies are resolved from its POM")
+                .long("extra-library")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("updateVerify")
+                .about("File or folder the launcher verifies before start")
+                .long("verify")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .default_values(&DEFAULT_UPDATE_VERIFY),
+        )
+        .arg(
+            Arg::new("updateExclusion")
+                .about("File or folder the launcher never overwrites")
+                .long("exclude")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .default_values(&DEFAULT_UPDATE_EXCLUSION),
+        )
+        .arg(
+            Arg::new("runtimePlatform")
+                .about("Platform to bundle the Java runtime of the version for, e.g. linux, windows-x64 or mac-os")
+                .long("runtime-platform")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .default_values(&DEFAULT_RUNTIME_PLATFORMS),
+        )
+        .arg(
+            Arg::new("runtimeArchives")
+                .about("Folder with <component>-<platform>.zip runtime archives, used instead of Mojang downloads")
+                .long("runtime-archives")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("noRuntime")
+                .about("Don't bundle a Java runtime")
+                .long("no-runtime")
+                .conflicts_with("runtimeArchives"),
+        )
+        .arg(
+            Arg::new("output")
+                .about("Folder with shared libraries, assets and natives, the profile goes to its <name> subfolder [default: <name>]")
+                .short('o')
+                .long("output")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("config")
+                .about("Profile definition file, replaces generation flags")
+                .long("config")
+                .takes_value(true)
+                .conflicts_with_all(&[
+                    "version",
+                    "profileName",
+                    "serverName",
+                    "serverPort",
+                    "forge",
+                    "fabric",
+                    "liteloader",
+                    "optifine",
+                    "repository",
+                    "extraLibrary",
+                    "runtimeArchives",
+                    "noRuntime",
+                ]),
+        )
+        .arg(
+            Arg::new("dryRun")
+                .about("Resolve versions and print the plan without downloading anything")
+                .long("dry-run"),
+        )
+        .arg(
+            Arg::new("locked")
+                .about("Fail unless resolved versions and hashes match the profile lockfile")
+                .long("locked"),
+        )
+        .arg(
+            Arg::new("format")
+                .about("Format of the dry run plan, text by default")
+                .long("format")
+                .takes_value(true)
+                .requires("dryRun")
+                .possible_values(&["text", "json"]),
+        )
+        .subcommand(
+            App::new("index")
+                .about("Regenerate the file index of a profile after manual edits")
+                .arg(
+                    Arg::new("dir")
+                        .required(true)
+                        .index(1)
+                        .about("Profile folder with profile.json"),
+                ),
+        )
+        .subcommand(
+            App::new("verify")
+                .about("Check that all files of a profile exist and are intact")
+                .arg(
+                    Arg::new("dir")
+                        .required(true)
+                        .index(1)
+                        .about("Profile folder with profile.json"),
+                )
+                .arg(
+                    Arg::new("repair")
+                        .long("repair")
+                        .about("Download missing and broken files"),
+                )
+                .arg(
+                    Arg::new("repository")
+                        .about("Maven repository to look up libraries in, in order of preference")
+                        .long("repository")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                ),
+        )
+        .subcommand(
+            App::new("update")
+                .about("Change game or loader version of an existing profile")
+                .arg(
+                    Arg::new("dir")
+                        .required(true)
+                        .index(1)
+                        .about("Profile folder with profile.json"),
+                )
+                .arg(
+                    Arg::new("version")
+                        .short('v')
+                        .long("version")
+                        .takes_value(true)
+                        .about("New Minecraft Version"),
+                )
+                .arg(
+                    Arg::new("forge")
+                        .about("New Forge Version")
+                        .long("forge")
+                        .takes_value(true)
+                        .conflicts_with("fabric")
+                        .validator(validator::correct_forge_version),
+                )
+                .arg(
+                    Arg::new("fabric")
+                        .about("New Fabric Loader Version")
+                        .long("fabric")
+                        .takes_value(true)
+                        .conflicts_with("forge")
+                        .validator(validator::correct_fabric_version),
+                )
+                .arg(
+                    Arg::new("repository")
+                        .about("Maven repository to look up libraries in, in order of preference")
+                        .long("repository")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                )
+                .arg(
+                    Arg::new("extraLibrary")
+                        .about("Additional library group:artifact:version, runtime dependencies are resolved from its POM")
+                        .long("extra-library")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                ),
+        )
+        .subcommand(
+            App::new("gc")
+                .about("Remove libraries and assets no profile references")
+                .arg(
+                    Arg::new("dir")
+                        .required(true)
+                        .index(1)
+                        .about("Folder with profiles, libraries and assets"),
+                )
+                .arg(
+                    Arg::new("dryRun")
+                        .long("dry-run")
+                        .about("Only list files that would be removed"),
+                ),
+        )
+        .subcommand(
+            App::new("batch")
+                .about("Generate every profile of a workspace file")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .index(1)
+                        .about("Workspace file with profile definitions"),
+                )
+                .arg(
+                    Arg::new("dryRun")
+                        .long("dry-run")
+                        .about("Only print what would change"),
+                )
+                .arg(
+                    Arg::new("locked")
+                        .long("locked")
+                        .about("Fail unless profiles match their lockfiles"),
+                ),
+        )
         .get_matches();
-    let profile_name = matches
-        .value_of("profileName")
-        .expect("Can't get profile name");
-    let game_version = matches.value_of("version").expect("Can't get version");
-    let game_libraries = matches
-        .value_of_t::<Libraries>("version")
-        .unwrap_or_else(|e| e.exit());
-    let address = matches
-        .value_of("serverName")
-        .expect("Can't get server name");
-    let port = matches
-        .value_of_t::<u32>("serverPort")
-        .unwrap_or_else(|e| e.exit());
-    let fabric = matches.value_of_t::<FabricLoaderManifest>("fabric");
-    let forge = matches.value_of_t::<ForgeManifest>("forge");
-    let game_type = if let Ok(manifest) = fabric {
-        GameType::Fabric(manifest)
-    } else if let Ok(manifest) = forge {
-        GameType::Forge(manifest)
+    match matches.subcommand() {
+        Some(("index", matches)) => index(matches),
+        Some(("verify", matches)) => verify(matches),
+        Some(("update", matches)) => update(matches),
+        Some(("gc", matches)) => gc(matches),
+        Some(("batch", matches)) => batch(matches),
+        _ => generate(&matches),
+    }
+}
+
+fn index(matches: &ArgMatches) {
+    let profile_folder = PathBuf::from(matches.value_of("dir").expect("Can't get profile folder"));
+    let base = profile_folder.parent().unwrap_or_else(|| Path::new(""));
+    // files from the previous index are kept while they exist
+    let previous: Vec<String> = index::read_index(&profile_folder)
+        .map(|v| v.files)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.path)
+        .filter(|v| base.join(v).is_file())
+        .collect();
+    let index = or_exit(
+        index::write_index(base, &profile_folder, &previous),
+        "Can't generate index",
+    );
+    log::info!("Indexed {} files", index.files.len());
+}
+
+fn verify(matches: &ArgMatches) {
+    let profile_folder = PathBuf::from(matches.value_of("dir").expect("Can't get profile folder"));
+    let base = profile_folder.parent().unwrap_or_else(|| Path::new(""));
+    let mut problems = or_exit(
+        verify::verify(base, &profile_folder),
+        "Can't verify profile",
+    );
+    if matches.is_present("repair") && !problems.is_empty() {
+        let report = or_exit(
+            verify::repair(base, &profile_folder, problems, &resolver(matches)),
+            "Can't repair profile",
+        );
+        for failure in &report.failures {
+            println!("{}", failure);
+        }
+        problems = report.remaining;
+    }
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if !problems.is_empty() {
+        std::process::exit(1);
+    }
+    println!("Profile is intact");
+}
+
+fn update(matches: &ArgMatches) {
+    let profile_folder = PathBuf::from(matches.value_of("dir").expect("Can't get profile folder"));
+    let base = profile_folder.parent().unwrap_or_else(|| Path::new(""));
+    let profile = or_exit(index::read_profile(&profile_folder), "Can't read profile");
+    or_exit(
+        update::check_profile_folder(&profile_folder, &profile),
+        "Can't update profile",
+    );
+    let old_files =
+        update::library_files(&profile, index::read_index(&profile_folder).ok().as_ref());
+    let game_version = matches.value_of("version").unwrap_or(&profile.version);
+    let loader = if let Some(version) = matches.value_of("fabric") {
+        Loader::Fabric(version.to_string())
+    } else if let Some(version) = matches.value_of("forge") {
+        Loader::Forge(version.to_string())
+    } else {
+        let loader = update::detect_loader(&profile);
+        log::info!("Keep loader {:?}", loader);
+        loader
+    };
+    let generator = ProfileGenerator::new()
+        .name(&profile.name)
+        .version(game_version)
+        .loader(loader)
+        .server(&profile.server_name, profile.server_port)
+        .output(base.to_path_buf())
+        .update_verify(profile.update_verify.clone())
+        .update_exclusion(profile.update_exclusion.clone());
+    // generated JVM arguments are not passed back, the new version and loader bring their own
+    let generator = match Lockfile::read(&profile_folder) {
+        Ok(lock) => or_exit(
+            update::restore_inputs(generator, lock.inputs),
+            "Can't restore profile inputs",
+        ),
+        Err(err) => {
+            log::warn!(
+                "Can't read lockfile, addons, mods, extra libraries and JVM arguments are dropped: {}",
+                err
+            );
+            generator
+        }
+    };
+    or_exit(
+        with_libraries(matches, generator).generate(),
+        "Can't update profile",
+    );
+    let removed = or_exit(
+        update::remove_unreferenced(base, old_files),
+        "Can't remove old libraries",
+    );
+    for path in &removed {
+        log::info!("Removed {}", path);
+    }
+}
+
+fn gc(matches: &ArgMatches) {
+    let base = Path::new(matches.value_of("dir").expect("Can't get folder"));
+    let files = or_exit(
+        gc::unreferenced_files(base),
+        "Can't collect unreferenced files",
+    );
+    for (_, path) in &files {
+        println!("{}", path);
+    }
+    if matches.is_present("dryRun") {
+        println!("{} files would be removed", files.len());
+    } else {
+        println!("Removed {} files", gc::remove(base, &files));
+    }
+}
+
+fn batch(matches: &ArgMatches) {
+    let path = Path::new(matches.value_of("file").expect("Can't get workspace file"));
+    let workspace = or_exit(Workspace::read(path), "Can't read workspace");
+    // profiles share found libraries and http connections
+    let resolver = Resolver::default();
+    let pool = DownloadPool::default();
+    let mut summary = Vec::new();
+    let mut failed = Vec::new();
+    for config in &workspace.profiles {
+        log::info!("Generate profile {}...", config.name);
+        let old = index::read_profile(&workspace.output.join(&config.name)).ok();
+        let generated = config.generator().and_then(|v| {
+            v.resolver(resolver.clone())
+                .pool(pool.clone())
+                .output(workspace.output.clone())
+                .dry_run(matches.is_present("dryRun"))
+                .locked(matches.is_present("locked"))
+                .generate()
+        });
+        match generated {
+            Ok(plan) => summary.push(ProfileChanges::new(old.as_ref(), &plan.profile)),
+            Err(err) => {
+                log::error!("Can't generate profile {}: {}", config.name, err);
+                failed.push(&config.name);
+            }
+        }
+    }
+    for changes in &summary {
+        print!("{}", changes);
+    }
+    for name in &failed {
+        println!("{}: failed", name);
+    }
+    if !failed.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Prints a generation error without the panic noise, e.g. why a loader doesn't fit the game version.
+fn or_exit<T>(result: profile_generator::Result<T>, message: &str) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("{}: {}", message, err);
+        std::process::exit(1)
+    })
+}
+
+fn extra_libraries(matches: &ArgMatches) -> Vec<Artifact> {
+    if matches.is_present("extraLibrary") {
+        matches
+            .values_of_t::<Artifact>("extraLibrary")
+            .unwrap_or_else(|e| e.exit())
     } else {
-        GameType::Vanilla
+        Vec::new()
+    }
+}
+
+fn resolver(matches: &ArgMatches) -> Resolver {
+    match matches.values_of("repository") {
+        Some(repositories) => Resolver::new(repositories.map(str::to_string).collect()),
+        None => Resolver::default(),
+    }
+}
+
+fn generate(matches: &ArgMatches) {
+    let generator = match matches.value_of("config") {
+        Some(path) => or_exit(
+            ProfileConfig::read(Path::new(path)).and_then(|v| v.generator()),
+            "Can't read profile config",
+        ),
+        None => cli_generator(matches),
     };
-    generator::generate_profile(
-        profile_name,
-        game_version,
-        game_libraries,
-        address,
-        port,
-        game_type,
-    )
-    .expect("Can't generate profile");
+    let mut generator = generator
+        .dry_run(matches.is_present("dryRun"))
+        .locked(matches.is_present("locked"));
+    if let Some(output) = matches.value_of("output") {
+        generator = generator.output(PathBuf::from(output));
+    }
+    let plan = or_exit(generator.generate(), "Can't generate profile");
+    if matches.is_present("dryRun") {
+        print_plan(matches, &plan);
+    }
+}
+
+/// Generator built from generation flags.
+fn cli_generator(matches: &ArgMatches) -> ProfileGenerator {
+    let loader = if let Some(version) = matches.value_of("fabric") {
+        Loader::Fabric(version.to_string())
+    } else if let Some(version) = matches.value_of("forge") {
+        Loader::Forge(version.to_string())
+    } else {
+        Loader::Vanilla
+    };
+    let mut generator = ProfileGenerator::new()
+        .name(
+            matches
+                .value_of("profileName")
+                .expect("Can't get profile name"),
+        )
+        .version(matches.value_of("version").expect("Can't get version"))
+        .loader(loader)
+        .server(
+            matches
+                .value_of("serverName")
+                .expect("Can't get server name"),
+            matches
+                .value_of_t::<u32>("serverPort")
+                .unwrap_or_else(|e| e.exit()),
+        )
+        .update_verify(
+            matches
+                .values_of("updateVerify")
+                .map(|v| v.map(str::to_string).collect())
+                .unwrap_or_default(),
+        )
+        .update_exclusion(
+            matches
+                .values_of("updateExclusion")
+                .map(|v| v.map(str::to_string).collect())
+                .unwrap_or_default(),
+        );
+    if let Some(version) = matches.value_of("liteloader") {
+        generator = generator.liteloader(version);
+    }
+    if let Some(path) = matches.value_of("optifine") {
+        generator = generator.optifine(PathBuf::from(path));
+    }
+    if matches.is_present("noRuntime") {
+        generator = generator.runtime_platforms(Vec::new());
+    } else if let Some(platforms) = matches.values_of("runtimePlatform") {
+        generator = generator.runtime_platforms(platforms.map(str::to_string).collect());
+    }
+    if let Some(folder) = matches.value_of("runtimeArchives") {
+        generator = generator.runtime_archives(PathBuf::from(folder));
+    }
+    with_libraries(matches, generator)
+}
+
+/// Adds `--repository` and `--extra-library` values to the generator.
+fn with_libraries(matches: &ArgMatches, mut generator: ProfileGenerator) -> ProfileGenerator {
+    for repository in matches.values_of("repository").into_iter().flatten() {
+        generator = generator.repository(repository);
+    }
+    for artifact in extra_libraries(matches) {
+        generator = generator.extra_library(artifact);
+    }
+    generator
+}
+
+fn print_plan(matches: &ArgMatches, plan: &Plan) {
+    match matches.value_of("format") {
+        Some("json") => println!(
+            "{}",
+            serde_json::to_string_pretty(plan).expect("Can't serialize plan")
+        ),
+        _ => print!("{}", plan),
+    }
 }
@@ -0,0 +1,332 @@
+use crate::artifact::Artifact;
+use crate::error::{GeneratorError, Result};
+use crate::util::get_text;
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub mod pom;
+
+pub const MOJANG: &str = "https://libraries.minecraft.net/";
+pub const FORGE: &str = "https://maven.minecraftforge.net/";
+pub const FABRIC: &str = "https://maven.fabricmc.net/";
+pub const CENTRAL: &str = "https://repo.maven.apache.org/maven2/";
+const RESOLVE_WORKERS: usize = 8;
+
+/// Looks up libraries in an ordered list of repositories.
+/// The repository a manifest declares for a library is tried first,
+/// then the configured ones in order.
+#[derive(Clone)]
+pub struct Resolver {
+    repositories: Vec<String>,
+    client: reqwest::blocking::Client,
+    // artifact coordinate -> repository that served it, shared by clones
+    served: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl Resolver {
+    pub fn new(repositories: Vec<String>) -> Self {
+        Resolver {
+            repositories: repositories.iter().map(|v| normalize(v)).collect(),
+            client: reqwest::blocking::Client::new(),
+            served: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Resolver with other repositories that shares the client and the found libraries of this one.
+    pub fn with_repositories(&self, repositories: Vec<String>) -> Self {
+        Resolver {
+            repositories: repositories.iter().map(|v| normalize(v)).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Download url and path in the libraries folder of a maven library.
+    pub fn resolve(&self, declared: &str, name: &str) -> Result<(String, String)> {
+        let declared = normalize(declared);
+        let candidates: Vec<&String> = std::iter::once(&declared)
+            .chain(self.repositories.iter().filter(|v| **v != declared))
+            .collect();
+        let (repository, lib_path) = self.find(&candidates, &name.parse()?)?;
+        Ok((format!("{}{}", repository, lib_path), lib_path))
+    }
+
+    /// [`Resolver::resolve`] of `(declared repository, name)` pairs on several threads,
+    /// the results keep the order of `libraries`.
+    pub fn resolve_all(&self, libraries: &[(String, String)]) -> Result<Vec<(String, String)>> {
+        let mut threads = Vec::new();
+        for worker in 0..RESOLVE_WORKERS.min(libraries.len()) {
+            let resolver = self.clone();
+            let chunk: Vec<(usize, (String, String))> = libraries
+                .iter()
+                .cloned()
+                .enumerate()
+                .skip(worker)
+                .step_by(RESOLVE_WORKERS)
+                .collect();
+            threads.push(thread::spawn(move || {
+                chunk
+                    .into_iter()
+                    .map(|(i, (declared, name))| Ok((i, resolver.resolve(&declared, &name)?)))
+                    .collect::<Result<Vec<_>>>()
+            }));
+        }
+        let mut resolved = vec![None; libraries.len()];
+        let mut result = Ok(());
+        for thread in threads {
+            match thread.join().expect("Resolve thread panicked") {
+                Ok(found) => {
+                    for (i, library) in found {
+                        resolved[i] = Some(library);
+                    }
+                }
+                Err(err) if result.is_ok() => result = Err(err),
+                Err(_) => {}
+            }
+        }
+        result.map(|_| resolved.into_iter().flatten().collect())
+    }
+
+    /// Same as [`Resolver::resolve`] for libraries that don't declare a repository.
+    pub fn locate(&self, artifact: &Artifact) -> Result<(String, String)> {
+        let candidates: Vec<&String> = self.repositories.iter().collect();
+        let (repository, lib_path) = self.find(&candidates, artifact)?;
+        Ok((format!("{}{}", repository, lib_path), lib_path))
+    }
+
+    pub fn fetch_pom(&self, artifact: &Artifact) -> Result<String> {
+        let pom = Artifact {
+            classifier: None,
+            extension: Some("pom".to_string()),
+            ..artifact.clone()
+        };
+        let candidates: Vec<&String> = self.repositories.iter().collect();
+        let (repository, pom_path) = self.find(&candidates, &pom)?;
+        fetch_text(&repository, &pom_path)
+    }
+
+    fn find(&self, candidates: &[&String], artifact: &Artifact) -> Result<(String, String)> {
+        let key = artifact.to_string();
+        let served = self.served.lock().unwrap().get(&key).cloned();
+        // a repository found for another profile is only used if this one lists it
+        if let Some(repository) = served.filter(|v| candidates.contains(&v)) {
+            let lib_path = artifact_path(&repository, artifact)?;
+            return Ok((repository, lib_path));
+        }
+        for repository in candidates {
+            let lib_path = artifact_path(repository, artifact)?;
+            if self.exists(repository, &lib_path)? {
+                log::debug!("Library {} found in {}", artifact, repository);
+                self.served
+                    .lock()
+                    .unwrap()
+                    .insert(key, repository.to_string());
+                return Ok((repository.to_string(), lib_path));
+            }
+        }
+        Err(GeneratorError::LibraryNotFound(artifact.to_string()))
+    }
+
+    /// Only a 404 or 410 means the repository doesn't have the file, other failures are errors.
+    fn exists(&self, repository: &str, path: &str) -> Result<bool> {
+        if !is_remote(repository) {
+            return Ok(Path::new(repository.trim_start_matches("file://"))
+                .join(path)
+                .is_file());
+        }
+        let url = format!("{}{}", repository, path);
+        let response = self
+            .client
+            .head(&url)
+            .send()
+            .map_err(|e| GeneratorError::network(&url, e))?;
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            StatusCode::NOT_FOUND | StatusCode::GONE => Ok(false),
+            status => Err(GeneratorError::network(&url, status)),
+        }
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Resolver::new(vec![
+            MOJANG.to_string(),
+            FORGE.to_string(),
+            FABRIC.to_string(),
+            CENTRAL.to_string(),
+        ])
+    }
+}
+
+fn normalize(repository: &str) -> String {
+    if repository.ends_with('/') {
+        repository.to_string()
+    } else {
+        format!("{}/", repository)
+    }
+}
+
+/// Reads a file from a repository, `base_url` may also be a local directory
+/// that mirrors the repository layout.
+pub fn fetch_text(base_url: &str, path: &str) -> Result<String> {
+    if is_remote(base_url) {
+        get_text(&format!("{}{}", base_url, path))
+    } else {
+        let file = Path::new(base_url.trim_start_matches("file://")).join(path);
+        std::fs::read_to_string(&file).map_err(|e| GeneratorError::io(&file, e))
+    }
+}
+
+pub fn is_remote(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Path of the artifact inside the repository.
+/// Snapshots are mapped to the timestamped file listed in `maven-metadata.xml`,
+/// if the repository has no metadata the plain `-SNAPSHOT` file name is used.
+pub fn artifact_path(base_url: &str, artifact: &Artifact) -> Result<String> {
+    if !artifact.is_snapshot() {
+        return Ok(path_string(&artifact.to_path()));
+    }
+    let metadata_path = path_string(&artifact.metadata_path());
+    let metadata = match fetch_text(base_url, &metadata_path) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            log::warn!("Can't get snapshot metadata for {}: {}", artifact, err);
+            return Ok(path_string(&artifact.to_path()));
+        }
+    };
+    let path = match snapshot_version(&metadata, artifact)? {
+        Some(version) => {
+            log::debug!("Resolve {} to {}", artifact, version);
+            artifact.to_path_with_version(&version)
+        }
+        None => artifact.to_path(),
+    };
+    Ok(path_string(&path))
+}
+
+// artifact paths are built from strings, so they are always valid unicode
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn snapshot_version(metadata: &str, artifact: &Artifact) -> Result<Option<String>> {
+    let document = roxmltree::Document::parse(metadata)
+        .map_err(|e| GeneratorError::metadata(format!("{} maven-metadata.xml", artifact), e))?;
+    let snapshot = match document
+        .descendants()
+        .find(|v| v.has_tag_name("versioning"))
+        .and_then(|v| v.children().find(|v| v.has_tag_name("snapshot")))
+    {
+        Some(snapshot) => snapshot,
+        None => return Ok(None),
+    };
+    if child_text(snapshot, "localCopy") == Some("true") {
+        return Ok(None);
+    }
+    let extension = artifact.extension.as_deref().unwrap_or("jar");
+    let listed = document
+        .descendants()
+        .filter(|v| v.has_tag_name("snapshotVersion"))
+        .find(|v| {
+            child_text(*v, "extension") == Some(extension)
+                && child_text(*v, "classifier") == artifact.classifier.as_deref()
+        })
+        .and_then(|v| child_text(v, "value"));
+    if let Some(value) = listed {
+        return Ok(Some(value.to_string()));
+    }
+    // old metadata only has the latest timestamp and build number
+    let timestamp = child_text(snapshot, "timestamp");
+    let build_number = child_text(snapshot, "buildNumber");
+    Ok(match (timestamp, build_number) {
+        (Some(timestamp), Some(build_number)) => Some(format!(
+            "{}-{}-{}",
+            artifact.version.trim_end_matches("-SNAPSHOT"),
+            timestamp,
+            build_number
+        )),
+        _ => None,
+    })
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|v| v.has_tag_name(name))
+        .and_then(|v| v.text())
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"<metadata>
+  <groupId>com.example</groupId>
+  <artifactId>lib</artifactId>
+  <version>1.0-SNAPSHOT</version>
+  <versioning>
+    <snapshot>
+      <timestamp>20210101.120000</timestamp>
+      <buildNumber>3</buildNumber>
+    </snapshot>
+    <snapshotVersions>
+      <snapshotVersion>
+        <extension>jar</extension>
+        <value>1.0-20210101.120000-3</value>
+      </snapshotVersion>
+      <snapshotVersion>
+        <classifier>sources</classifier>
+        <extension>jar</extension>
+        <value>1.0-20201231.100000-2</value>
+      </snapshotVersion>
+    </snapshotVersions>
+  </versioning>
+</metadata>"#;
+
+    fn snapshot(coordinate: &str, metadata: &str) -> Option<String> {
+        snapshot_version(metadata, &coordinate.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn snapshot_version_from_listed_files() {
+        assert_eq!(
+            snapshot("com.example:lib:1.0-SNAPSHOT", METADATA).as_deref(),
+            Some("1.0-20210101.120000-3")
+        );
+        assert_eq!(
+            snapshot("com.example:lib:1.0-SNAPSHOT:sources", METADATA).as_deref(),
+            Some("1.0-20201231.100000-2")
+        );
+    }
+
+    #[test]
+    fn snapshot_version_from_old_metadata() {
+        let metadata = r#"<metadata><versioning><snapshot>
+            <timestamp>20190505.080000</timestamp><buildNumber>7</buildNumber>
+        </snapshot></versioning></metadata>"#;
+        assert_eq!(
+            snapshot("com.example:lib:2.1-SNAPSHOT", metadata).as_deref(),
+            Some("2.1-20190505.080000-7")
+        );
+    }
+
+    #[test]
+    fn snapshot_version_without_timestamp() {
+        let local = r#"<metadata><versioning><snapshot>
+            <localCopy>true</localCopy>
+        </snapshot></versioning></metadata>"#;
+        assert_eq!(snapshot("com.example:lib:1.0-SNAPSHOT", local), None);
+        let empty = "<metadata><versioning></versioning></metadata>";
+        assert_eq!(snapshot("com.example:lib:1.0-SNAPSHOT", empty), None);
+        assert!(snapshot_version(
+            "<metadata></versioning>",
+            &"com.example:lib:1.0-SNAPSHOT".parse().unwrap()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,402 @@
+use crate::artifact::Artifact;
+use crate::error::{GeneratorError, Result};
+use crate::maven::Resolver;
+use roxmltree::Node;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// scopes that end up on the runtime classpath
+const RUNTIME_SCOPES: [&str; 2] = ["compile", "runtime"];
+
+#[derive(Clone, Debug)]
+pub struct Dependency {
+    pub group: String,
+    pub artifact: String,
+    pub version: Option<String>,
+    pub classifier: Option<String>,
+    pub extension: Option<String>,
+    pub scope: Option<String>,
+    pub optional: bool,
+    pub exclusions: Vec<String>,
+}
+
+impl Dependency {
+    fn key(&self) -> String {
+        format!("{}:{}", self.group, self.artifact)
+    }
+
+    fn is_runtime(&self) -> bool {
+        !self.optional && RUNTIME_SCOPES.contains(&self.scope.as_deref().unwrap_or("compile"))
+    }
+}
+
+pub struct Pom {
+    pub group: Option<String>,
+    pub version: Option<String>,
+    pub parent: Option<Artifact>,
+    pub properties: HashMap<String, String>,
+    pub dependencies: Vec<Dependency>,
+    pub managed: Vec<Dependency>,
+}
+
+impl Pom {
+    pub fn parse(xml: &str) -> std::result::Result<Self, roxmltree::Error> {
+        let document = roxmltree::Document::parse(xml)?;
+        let project = document.root_element();
+        let parent = child(project, "parent").map(|parent| Artifact {
+            group: child_text(parent, "groupId").unwrap_or_default(),
+            artifact: child_text(parent, "artifactId").unwrap_or_default(),
+            version: child_text(parent, "version").unwrap_or_default(),
+            classifier: None,
+            extension: Some("pom".to_string()),
+        });
+        let properties = child(project, "properties")
+            .map(|v| {
+                v.children()
+                    .filter(Node::is_element)
+                    .map(|v| {
+                        let value = v.text().unwrap_or_default().trim().to_string();
+                        (v.tag_name().name().to_string(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let managed = child(project, "dependencyManagement")
+            .map(dependencies)
+            .unwrap_or_default();
+        Ok(Pom {
+            group: child_text(project, "groupId"),
+            version: child_text(project, "version"),
+            parent,
+            properties,
+            dependencies: dependencies(project),
+            managed,
+        })
+    }
+
+    fn fetch(resolver: &Resolver, artifact: &Artifact) -> Result<Self> {
+        Pom::parse(&resolver.fetch_pom(artifact)?)
+            .map_err(|e| GeneratorError::metadata(format!("{} POM", artifact), e))
+    }
+
+    /// Loads the POM of an artifact and merges properties and managed versions of its parents.
+    pub fn effective(resolver: &Resolver, artifact: &Artifact) -> Result<Self> {
+        let mut pom = Pom::fetch(resolver, artifact)?;
+        let mut parent = pom.parent.clone();
+        let mut depth = 0;
+        while let Some(parent_artifact) = parent {
+            depth += 1;
+            if depth > 16 {
+                return Err(GeneratorError::metadata(artifact, "too deep POM hierarchy"));
+            }
+            let parent_pom = Pom::fetch(resolver, &parent_artifact)?;
+            for (key, value) in parent_pom.properties {
+                pom.properties.entry(key).or_insert(value);
+            }
+            pom.managed.extend(parent_pom.managed);
+            if pom.group.is_none() {
+                pom.group = Some(parent_artifact.group.clone());
+            }
+            if pom.version.is_none() {
+                pom.version = Some(parent_artifact.version.clone());
+            }
+            pom.properties
+                .entry("project.parent.version".to_string())
+                .or_insert_with(|| parent_artifact.version.clone());
+            parent = parent_pom.parent;
+        }
+        let group = pom.group.clone().unwrap_or_else(|| artifact.group.clone());
+        let version = pom
+            .version
+            .clone()
+            .unwrap_or_else(|| artifact.version.clone());
+        for key in &["project.groupId", "pom.groupId", "groupId"] {
+            pom.properties.insert(key.to_string(), group.clone());
+        }
+        for key in &["project.version", "pom.version", "version"] {
+            pom.properties.insert(key.to_string(), version.clone());
+        }
+        pom.properties
+            .insert("project.artifactId".to_string(), artifact.artifact.clone());
+        Ok(pom)
+    }
+
+    /// Runtime dependencies with interpolated coordinates and versions filled from
+    /// `dependencyManagement`.
+    fn runtime_dependencies(&self) -> Vec<Dependency> {
+        self.dependencies
+            .iter()
+            .map(|v| self.interpolate(v))
+            .filter(Dependency::is_runtime)
+            .map(|mut v| {
+                if v.version.is_none() {
+                    v.version = self
+                        .managed
+                        .iter()
+                        .map(|m| self.interpolate(m))
+                        .find(|m| m.key() == v.key())
+                        .and_then(|m| m.version);
+                }
+                v
+            })
+            .collect()
+    }
+
+    fn interpolate(&self, dependency: &Dependency) -> Dependency {
+        let mut dependency = dependency.clone();
+        dependency.group = self.interpolate_str(&dependency.group);
+        dependency.artifact = self.interpolate_str(&dependency.artifact);
+        dependency.version = dependency.version.map(|v| self.interpolate_str(&v));
+        dependency.scope = dependency.scope.map(|v| self.interpolate_str(&v));
+        dependency
+    }
+
+    fn interpolate_str(&self, value: &str) -> String {
+        let mut result = value.to_string();
+        // properties may reference other properties
+        for _ in 0..8 {
+            let start = match result.find("${") {
+                Some(start) => start,
+                None => break,
+            };
+            let end = match result[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            let name = &result[start + 2..end];
+            let replacement = match self.properties.get(name) {
+                Some(replacement) => replacement.clone(),
+                None => break,
+            };
+            result.replace_range(start..=end, &replacement);
+        }
+        result
+    }
+}
+
+/// Runtime dependency closure of the root artifacts, the root artifacts come first.
+/// The nearest declaration of a library wins, like in Maven.
+pub fn resolve_dependencies(resolver: &Resolver, roots: &[Artifact]) -> Result<Vec<Artifact>> {
+    let mut resolved = Vec::new();
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<(Artifact, Vec<String>)> =
+        roots.iter().map(|v| (v.clone(), Vec::new())).collect();
+    while let Some((artifact, exclusions)) = queue.pop_front() {
+        if !visited.insert(format!("{}:{}", artifact.group, artifact.artifact)) {
+            continue;
+        }
+        let pom = match Pom::effective(resolver, &artifact) {
+            Ok(pom) => Some(pom),
+            Err(err) => {
+                log::warn!("Can't read POM of {}: {}", artifact, err);
+                None
+            }
+        };
+        if let Some(pom) = pom {
+            for dependency in pom.runtime_dependencies() {
+                if is_excluded(&exclusions, &dependency) {
+                    continue;
+                }
+                if dependency.extension.as_deref() == Some("pom") {
+                    log::debug!("Skip POM dependency {} of {}", dependency.key(), artifact);
+                    continue;
+                }
+                let version = match dependency.version {
+                    Some(ref version) => version.clone(),
+                    None => {
+                        log::warn!("No version for {} in {}", dependency.key(), artifact);
+                        continue;
+                    }
+                };
+                let mut dependency_exclusions = exclusions.clone();
+                dependency_exclusions.extend(dependency.exclusions.iter().cloned());
+                queue.push_back((
+                    Artifact {
+                        group: dependency.group,
+                        artifact: dependency.artifact,
+                        version,
+                        classifier: dependency.classifier,
+                        extension: dependency.extension.filter(|v| v != "jar" && v != "bundle"),
+                    },
+                    dependency_exclusions,
+                ));
+            }
+        }
+        resolved.push(artifact);
+    }
+    Ok(resolved)
+}
+
+fn is_excluded(exclusions: &[String], dependency: &Dependency) -> bool {
+    exclusions.iter().any(|v| {
+        *v == dependency.key()
+            || *v == format!("{}:*", dependency.group)
+            || *v == format!("*:{}", dependency.artifact)
+            || v == "*:*"
+    })
+}
+
+fn dependencies(node: Node) -> Vec<Dependency> {
+    let dependencies = match child(node, "dependencies") {
+        Some(dependencies) => dependencies,
+        None => return Vec::new(),
+    };
+    dependencies
+        .children()
+        .filter(|v| v.has_tag_name("dependency"))
+        .map(|v| Dependency {
+            group: child_text(v, "groupId").unwrap_or_default(),
+            artifact: child_text(v, "artifactId").unwrap_or_default(),
+            version: child_text(v, "version"),
+            classifier: child_text(v, "classifier"),
+            extension: child_text(v, "type"),
+            scope: child_text(v, "scope"),
+            optional: child_text(v, "optional").as_deref() == Some("true"),
+            exclusions: child(v, "exclusions")
+                .map(|v| {
+                    v.children()
+                        .filter(|v| v.has_tag_name("exclusion"))
+                        .map(|v| {
+                            format!(
+                                "{}:{}",
+                                child_text(v, "groupId").unwrap_or_default(),
+                                child_text(v, "artifactId").unwrap_or_default()
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|v| v.has_tag_name(name))
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|v| v.text())
+        .map(|v| v.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+    use std::path::{Path, PathBuf};
+
+    fn pom(properties: &[(&str, &str)]) -> Pom {
+        Pom {
+            group: None,
+            version: None,
+            parent: None,
+            properties: properties
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            dependencies: Vec::new(),
+            managed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn interpolates_properties() {
+        let pom = pom(&[
+            ("asm.version", "9.1"),
+            ("lib.version", "${asm.version}-custom"),
+        ]);
+        assert_eq!(pom.interpolate_str("${asm.version}"), "9.1");
+        assert_eq!(pom.interpolate_str("v${lib.version}"), "v9.1-custom");
+        assert_eq!(pom.interpolate_str("${unknown}"), "${unknown}");
+        assert_eq!(pom.interpolate_str("${asm.version"), "${asm.version");
+        assert_eq!(pom.interpolate_str("plain"), "plain");
+    }
+
+    fn project(artifact: &str, dependencies: &str) -> String {
+        format!(
+            "<project><groupId>com.example</groupId><artifactId>{}</artifactId>\
+             <version>1.0</version><properties><b.version>2.0</b.version></properties>\
+             <dependencies>{}</dependencies></project>",
+            artifact, dependencies
+        )
+    }
+
+    fn dependency(artifact: &str, version: &str, extra: &str) -> String {
+        format!(
+            "<dependency><groupId>com.example</groupId><artifactId>{}</artifactId>\
+             <version>{}</version>{}</dependency>",
+            artifact, version, extra
+        )
+    }
+
+    fn write_pom(repository: &Path, artifact: &str, version: &str, xml: &str) {
+        let folder = repository.join("com/example").join(artifact).join(version);
+        create_dir_all(&folder).unwrap();
+        write(folder.join(format!("{}-{}.pom", artifact, version)), xml).unwrap();
+    }
+
+    #[test]
+    fn resolves_dependencies_with_exclusions() {
+        let repository: PathBuf =
+            std::env::temp_dir().join(format!("profile-generator-pom-{}", std::process::id()));
+        let exclusion = "<exclusions><exclusion><groupId>com.example</groupId>\
+                         <artifactId>lib-c</artifactId></exclusion></exclusions>";
+        let app = [
+            dependency("lib-a", "1.0", exclusion),
+            dependency("lib-b", "${b.version}", ""),
+            dependency("junit", "4.13", "<scope>test</scope>"),
+            dependency("lib-o", "1.0", "<optional>true</optional>"),
+        ];
+        write_pom(&repository, "app", "1.0", &project("app", &app.concat()));
+        let lib_a = [
+            dependency("lib-c", "1.0", ""),
+            dependency("lib-d", "1.0", ""),
+        ];
+        write_pom(
+            &repository,
+            "lib-a",
+            "1.0",
+            &project("lib-a", &lib_a.concat()),
+        );
+        write_pom(&repository, "lib-b", "2.0", &project("lib-b", ""));
+        write_pom(&repository, "lib-c", "1.0", &project("lib-c", ""));
+        let lib_d = dependency("lib-c", "1.0", "");
+        write_pom(&repository, "lib-d", "1.0", &project("lib-d", &lib_d));
+
+        let resolver = Resolver::new(vec![format!("file://{}", repository.display())]);
+        let resolved =
+            resolve_dependencies(&resolver, &["com.example:app:1.0".parse().unwrap()]).unwrap();
+        remove_dir_all(&repository).unwrap();
+        let resolved: Vec<String> = resolved.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            resolved,
+            vec![
+                "com.example:app:1.0",
+                "com.example:lib-a:1.0",
+                "com.example:lib-b:2.0",
+                "com.example:lib-d:1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_exclusion_patterns() {
+        let dependency = Dependency {
+            group: "com.example".to_string(),
+            artifact: "lib".to_string(),
+            version: None,
+            classifier: None,
+            extension: None,
+            scope: None,
+            optional: false,
+            exclusions: Vec::new(),
+        };
+        for pattern in &["com.example:lib", "com.example:*", "*:lib", "*:*"] {
+            assert!(is_excluded(&[pattern.to_string()], &dependency));
+        }
+        assert!(!is_excluded(
+            &["com.example:other".to_string()],
+            &dependency
+        ));
+    }
+}
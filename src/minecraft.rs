@@ -1,10 +1,17 @@
 use crate::minecraft::fabric::FabricLoaderManifest;
 use crate::minecraft::forge::ForgeManifest;
+use crate::minecraft::liteloader::LiteLoaderManifest;
+use crate::minecraft::optifine::OptiFine;
 
 pub mod assets;
 pub mod fabric;
 pub mod forge;
 pub mod libraries;
+pub mod liteloader;
+pub mod multimc;
+pub mod optifine;
+pub mod runtime;
+pub mod tweaker;
 pub mod version;
 
 pub enum GameType {
@@ -12,3 +19,10 @@ pub enum GameType {
     Forge(ForgeManifest),
     Fabric(FabricLoaderManifest),
 }
+
+/// Optional components installed on top of vanilla or Forge.
+#[derive(Default)]
+pub struct Addons {
+    pub liteloader: Option<LiteLoaderManifest>,
+    pub optifine: Option<OptiFine>,
+}
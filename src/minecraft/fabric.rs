@@ -1,8 +1,11 @@
+use crate::error::{GeneratorError, Result};
 use crate::minecraft::version::NameLibrary;
-use anyhow::{Error, Result};
+use crate::util::{get_latest_json, get_optional_json};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
+const FABRIC_META_URL: &str = "https://meta.fabricmc.net/v2";
+
 #[derive(Deserialize, Serialize)]
 pub struct FabricLoaderManifest {
     pub version: i32,
@@ -22,15 +25,26 @@ pub struct FabricLibraries {
     pub common: Vec<NameLibrary>,
 }
 
+#[derive(Deserialize)]
+struct GameVersion {
+    version: String,
+}
+
+/// Whether Fabric has intermediary mappings for the Minecraft version.
+pub fn supports_game_version(game_version: &str) -> Result<bool> {
+    let url = format!("{}/versions/game", FABRIC_META_URL);
+    let versions: Vec<GameVersion> = get_latest_json(&url)?;
+    Ok(versions.iter().any(|v| v.version == game_version))
+}
+
 impl FromStr for FabricLoaderManifest {
-    type Err = Error;
+    type Err = GeneratorError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> Result<Self> {
         let url: String = format!(
             "https://maven.fabricmc.net/net/fabricmc/fabric-loader/{ver}/fabric-loader-{ver}.json",
             ver = s
         );
-        let manifest = reqwest::blocking::get(&url)?.json::<FabricLoaderManifest>()?;
-        Ok(manifest)
+        get_optional_json(&url)?.ok_or_else(|| GeneratorError::unsupported_version("fabric", s))
     }
 }
@@ -1,6 +1,8 @@
+use crate::error::{GeneratorError, Result};
 use crate::minecraft::libraries::Library;
+use crate::minecraft::multimc::{required_minecraft, Requirement};
 use crate::minecraft::version::NameLibrary;
-use anyhow::{Error, Result};
+use crate::util::get_optional_json;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -13,6 +15,42 @@ pub struct ForgeManifest {
     #[serde(rename = "mavenFiles")]
     pub maven_files: Option<Vec<Library>>,
     pub libraries: Vec<LibraryType>,
+    pub requires: Option<Vec<Requirement>>,
+    pub order: Option<i32>,
+    #[serde(rename = "+jvmArgs")]
+    pub jvm_args: Option<Vec<String>>,
+    #[serde(rename = "+traits")]
+    pub traits: Option<Vec<String>>,
+    #[serde(rename = "minecraftArguments")]
+    pub minecraft_arguments: Option<String>,
+}
+
+impl ForgeManifest {
+    pub fn minecraft_version(&self) -> Option<&str> {
+        self.requires.as_deref().and_then(required_minecraft)
+    }
+
+    /// Arguments from `minecraftArguments` that the launcher does not fill in itself.
+    pub fn client_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(arguments) = &self.minecraft_arguments {
+            let parts: Vec<&str> = arguments.split_whitespace().collect();
+            let mut index = 0;
+            while index < parts.len() {
+                let key = parts[index];
+                let value = parts.get(index + 1).filter(|v| !v.starts_with("--"));
+                index += if value.is_some() { 2 } else { 1 };
+                if value.map(|v| v.contains("${")).unwrap_or(false) {
+                    continue;
+                }
+                args.push(key.to_string());
+                if let Some(value) = value {
+                    args.push(value.to_string());
+                }
+            }
+        }
+        args
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -22,15 +60,23 @@ pub enum LibraryType {
     NameLibrary(NameLibrary),
 }
 
+impl LibraryType {
+    pub fn name(&self) -> &str {
+        match self {
+            LibraryType::PathLibrary(v) => &v.name,
+            LibraryType::NameLibrary(v) => &v.name,
+        }
+    }
+}
+
 impl FromStr for ForgeManifest {
-    type Err = Error;
+    type Err = GeneratorError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> Result<Self> {
         let url: String = format!(
             "https://meta.multimc.org/v1/net.minecraftforge/{ver}.json",
             ver = s
         );
-        let manifest = reqwest::blocking::get(&url)?.json::<ForgeManifest>()?;
-        Ok(manifest)
+        get_optional_json(&url)?.ok_or_else(|| GeneratorError::unsupported_version("forge", s))
     }
 }
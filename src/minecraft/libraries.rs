@@ -35,6 +35,7 @@ pub struct File {
     pub path: Option<String>,
     pub url: String,
     pub sha1: String,
+    pub size: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
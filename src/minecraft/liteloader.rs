@@ -0,0 +1,41 @@
+use crate::error::{GeneratorError, Result};
+use crate::minecraft::forge::LibraryType;
+use crate::minecraft::multimc::{required_minecraft, Requirement};
+use crate::util::get_optional_json;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Deserialize, Serialize)]
+pub struct LiteLoaderManifest {
+    #[serde(rename = "mainClass")]
+    pub main_class: Option<String>,
+    #[serde(rename = "+tweakers")]
+    pub tweakers: Option<Vec<String>>,
+    pub libraries: Vec<LibraryType>,
+    pub order: Option<i32>,
+    pub requires: Option<Vec<Requirement>>,
+    /// Requested LiteLoader version.
+    #[serde(skip)]
+    pub version: String,
+}
+
+impl LiteLoaderManifest {
+    pub fn minecraft_version(&self) -> Option<&str> {
+        self.requires.as_deref().and_then(required_minecraft)
+    }
+}
+
+impl FromStr for LiteLoaderManifest {
+    type Err = GeneratorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let url: String = format!(
+            "https://meta.multimc.org/v1/com.mumfrey.liteloader/{ver}.json",
+            ver = s
+        );
+        let mut manifest: LiteLoaderManifest = get_optional_json(&url)?
+            .ok_or_else(|| GeneratorError::unsupported_version("liteloader", s))?;
+        manifest.version = s.to_string();
+        Ok(manifest)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::error::Result;
+use crate::minecraft::forge::LibraryType;
+use crate::minecraft::libraries::Library;
+use crate::util::get_json;
+use crate::util::library_key;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const META_URL: &str = "https://meta.multimc.org/v1";
+const MINECRAFT_COMPONENT: &str = "net.minecraft";
+const LWJGL_COMPONENTS: [&str; 2] = ["org.lwjgl", "org.lwjgl3"];
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Requirement {
+    pub uid: String,
+    pub equals: Option<String>,
+    pub suggests: Option<String>,
+}
+
+impl Requirement {
+    pub fn version(&self) -> Option<&str> {
+        self.equals.as_deref().or(self.suggests.as_deref())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Component {
+    pub uid: String,
+    pub version: String,
+    #[serde(default)]
+    pub requires: Vec<Requirement>,
+    #[serde(default)]
+    pub libraries: Vec<LibraryType>,
+}
+
+impl Component {
+    pub fn fetch(uid: &str, version: &str) -> Result<Self> {
+        let url = format!(
+            "{meta}/{uid}/{ver}.json",
+            meta = META_URL,
+            uid = uid,
+            ver = version
+        );
+        get_json(&url)
+    }
+
+    pub fn is_lwjgl(&self) -> bool {
+        LWJGL_COMPONENTS.contains(&self.uid.as_str())
+    }
+}
+
+/// Minecraft version a component is built for.
+pub fn required_minecraft(requires: &[Requirement]) -> Option<&str> {
+    requires
+        .iter()
+        .find(|v| v.uid == MINECRAFT_COMPONENT)
+        .and_then(|v| v.equals.as_deref())
+}
+
+/// Walks the `requires` graph and fetches every component it references.
+pub fn resolve_components(requires: &[Requirement]) -> Result<Vec<Component>> {
+    let mut components = Vec::new();
+    let mut visited = HashSet::new();
+    let mut queue = requires.to_vec();
+    while let Some(requirement) = queue.pop() {
+        if !visited.insert(requirement.uid.clone()) {
+            continue;
+        }
+        let version = match requirement.version() {
+            Some(version) => version,
+            None => {
+                log::warn!("Component {} has no required version", requirement.uid);
+                continue;
+            }
+        };
+        let component = Component::fetch(&requirement.uid, version)?;
+        queue.extend(component.requires.iter().cloned());
+        components.push(component);
+    }
+    Ok(components)
+}
+
+/// Replaces vanilla LWJGL libraries with the ones from resolved LWJGL components.
+pub fn apply_components(libraries: &mut Vec<Library>, components: Vec<Component>) {
+    for component in components.into_iter().filter(Component::is_lwjgl) {
+        log::info!("Use {} {} libraries", component.uid, component.version);
+        let replacement: Vec<Library> = component
+            .libraries
+            .into_iter()
+            .filter_map(|v| match v {
+                LibraryType::PathLibrary(v) => Some(v),
+                _ => None,
+            })
+            .collect();
+        let replaced: HashSet<&str> = replacement.iter().map(|v| library_key(&v.name)).collect();
+        libraries.retain(|v| {
+            let key = library_key(&v.name);
+            !(key.starts_with("org.lwjgl") || replaced.contains(key))
+        });
+        libraries.extend(replacement);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::error::{GeneratorError, Result};
+use std::fs::{copy, create_dir_all, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use zip::ZipArchive;
+
+pub const TWEAKER: &str = "optifine.OptiFineTweaker";
+pub const LAUNCHWRAPPER_MAIN_CLASS: &str = "net.minecraft.launchwrapper.Launch";
+
+pub const LAUNCHWRAPPER: &str = "net.minecraft:launchwrapper:1.12";
+pub const LAUNCHWRAPPER_URL: &str =
+    "https://libraries.minecraft.net/net/minecraft/launchwrapper/1.12/launchwrapper-1.12.jar";
+const LAUNCHWRAPPER_PATH: &str = "net/minecraft/launchwrapper/1.12/launchwrapper-1.12.jar";
+
+pub struct OptiFine {
+    pub path: PathBuf,
+    pub version: String,
+}
+
+impl OptiFine {
+    pub fn library_path(&self) -> String {
+        format!(
+            "optifine/OptiFine/{ver}/OptiFine-{ver}.jar",
+            ver = self.version
+        )
+    }
+
+    /// Copies the installer jar to the `mods` folder, Forge loads it as a regular mod.
+    pub fn install_mod(&self, mods_folder: &Path) -> Result<String> {
+        create_dir_all(mods_folder).map_err(|e| GeneratorError::io(mods_folder, e))?;
+        let file_name = format!("OptiFine_{}.jar", self.version);
+        copy(&self.path, mods_folder.join(&file_name))
+            .map_err(|e| GeneratorError::io(&self.path, e))?;
+        Ok(file_name)
+    }
+
+    /// Names and paths of libraries the patched client needs.
+    pub fn libraries(&self) -> Result<Vec<(String, String)>> {
+        let mut libraries = vec![(
+            format!("optifine:OptiFine:{}", self.version),
+            self.library_path(),
+        )];
+        match self.launchwrapper_version()? {
+            Some(version) => libraries.push((
+                format!("optifine:launchwrapper-of:{}", version),
+                launchwrapper_of_path(&version),
+            )),
+            None => libraries.push((LAUNCHWRAPPER.to_string(), LAUNCHWRAPPER_PATH.to_string())),
+        }
+        Ok(libraries)
+    }
+
+    /// Patches the client jar with the OptiFine patcher and extracts the LaunchWrapper it ships.
+    /// The vanilla LaunchWrapper is downloaded separately, see [`OptiFine::libraries`].
+    pub fn install_library(&self, client_jar: &Path, libraries_folder: &Path) -> Result<()> {
+        let output = libraries_folder.join(self.library_path());
+        create_parent(&output)?;
+        let status = Command::new("java")
+            .arg("-cp")
+            .arg(&self.path)
+            .arg("optifine.Patcher")
+            .arg(client_jar)
+            .arg(&self.path)
+            .arg(&output)
+            .status()
+            .map_err(|e| self.install_error(format!("can't run java: {}", e)))?;
+        if !status.success() {
+            return Err(self.install_error(format!("patcher exited with {}", status)));
+        }
+        if let Some(version) = self.launchwrapper_version()? {
+            let output = libraries_folder.join(launchwrapper_of_path(&version));
+            create_parent(&output)?;
+            let mut jar = self.open()?;
+            let mut file = jar
+                .by_name(&format!("launchwrapper-of-{}.jar", version))
+                .map_err(|e| GeneratorError::io(&self.path, e.into()))?;
+            let mut target = File::create(&output).map_err(|e| GeneratorError::io(&output, e))?;
+            io::copy(&mut file, &mut target).map_err(|e| GeneratorError::io(&output, e))?;
+        }
+        Ok(())
+    }
+
+    fn launchwrapper_version(&self) -> Result<Option<String>> {
+        let mut jar = self.open()?;
+        let version = match jar.by_name("launchwrapper-of.txt") {
+            Ok(mut file) => {
+                let mut version = String::new();
+                file.read_to_string(&mut version)
+                    .map_err(|e| GeneratorError::io(&self.path, e))?;
+                Some(version.trim().to_string())
+            }
+            Err(_) => None,
+        };
+        Ok(version)
+    }
+
+    fn open(&self) -> Result<ZipArchive<File>> {
+        open_jar(&self.path)
+    }
+
+    fn install_error(&self, reason: String) -> GeneratorError {
+        GeneratorError::Install {
+            component: format!("OptiFine {}", self.version),
+            reason,
+        }
+    }
+}
+
+fn open_jar(path: &Path) -> Result<ZipArchive<File>> {
+    let file = File::open(path).map_err(|e| GeneratorError::io(path, e))?;
+    ZipArchive::new(file).map_err(|e| GeneratorError::io(path, e.into()))
+}
+
+fn create_parent(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).map_err(|e| GeneratorError::io(parent, e))?;
+    }
+    Ok(())
+}
+
+fn launchwrapper_of_path(version: &str) -> String {
+    format!(
+        "optifine/launchwrapper-of/{ver}/launchwrapper-of-{ver}.jar",
+        ver = version
+    )
+}
+
+impl FromStr for OptiFine {
+    type Err = GeneratorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let path = PathBuf::from(s);
+        let mut jar = open_jar(&path)?;
+        // the first changelog line looks like "OptiFine 1.12.2_HD_U_G5"
+        let from_changelog = match jar.by_name("changelog.txt") {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .next()
+                .transpose()
+                .map_err(|e| GeneratorError::io(&path, e))?
+                .and_then(|line| line.strip_prefix("OptiFine ").map(str::to_string)),
+            Err(_) => None,
+        };
+        let version = match from_changelog {
+            Some(version) => version,
+            None => path
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .map(|v| v.trim_start_matches("preview_"))
+                .and_then(|v| v.strip_prefix("OptiFine_"))
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    GeneratorError::metadata(path.display(), "can't detect OptiFine version")
+                })?,
+        };
+        Ok(OptiFine { path, version })
+    }
+}
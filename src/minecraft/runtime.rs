@@ -0,0 +1,233 @@
+use crate::download::DownloadPool;
+use crate::error::{GeneratorError, Result};
+use crate::index::relative;
+use crate::plan::PlannedFile;
+use crate::util::{get_json, get_latest_json};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use zip::ZipArchive;
+
+const RUNTIME_MANIFEST_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+pub const DEFAULT_RUNTIME_PLATFORMS: [&str; 3] = ["linux", "windows-x64", "mac-os"];
+/// Runtime locations written next to `profile.json`.
+pub const RUNTIME_FILE: &str = "runtime.json";
+
+/// `javaVersion` of a version JSON.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct JavaVersion {
+    pub component: String,
+    #[serde(rename = "majorVersion")]
+    pub major_version: u32,
+}
+
+#[derive(Deserialize)]
+struct RuntimeRelease {
+    manifest: RuntimeDownload,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RuntimeDownload {
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RuntimeDownloads {
+    pub raw: RuntimeDownload,
+}
+
+/// Entry of a runtime component manifest, paths are relative to the runtime folder.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RuntimeEntry {
+    File {
+        #[serde(default)]
+        executable: bool,
+        downloads: RuntimeDownloads,
+    },
+    Directory,
+    Link {
+        target: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct RuntimeManifest {
+    files: BTreeMap<String, RuntimeEntry>,
+}
+
+/// Java runtime of one platform, unpacked to `path`.
+#[derive(Serialize, Clone)]
+pub struct PlannedRuntime {
+    pub platform: String,
+    pub component: String,
+    pub major_version: u32,
+    /// Folder relative to the output folder, e.g. `runtime/linux/java-runtime-alpha`.
+    pub path: String,
+    /// Local archive used instead of the Mojang runtime files.
+    pub archive: Option<PathBuf>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, RuntimeEntry>,
+}
+
+/// Entry of `runtime.json`.
+#[derive(Serialize, Deserialize)]
+pub struct RuntimeLocation {
+    pub platform: String,
+    pub component: String,
+    pub major_version: u32,
+    pub path: String,
+}
+
+impl PlannedRuntime {
+    /// Files downloaded from Mojang, with hashes.
+    pub fn planned_files(&self) -> Vec<PlannedFile> {
+        self.files
+            .iter()
+            .filter_map(|(path, entry)| match entry {
+                RuntimeEntry::File { downloads, .. } => Some(PlannedFile {
+                    url: downloads.raw.url.to_string(),
+                    path: format!("{}/{}", self.path, path),
+                    sha1: Some(downloads.raw.sha1.to_string()),
+                    size: Some(downloads.raw.size),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn location(&self) -> RuntimeLocation {
+        RuntimeLocation {
+            platform: self.platform.to_string(),
+            component: self.component.to_string(),
+            major_version: self.major_version,
+            path: self.path.to_string(),
+        }
+    }
+}
+
+/// Resolves the runtime of every platform, an archive named `<component>-<platform>.zip`
+/// in `archives` is preferred over the Mojang download.
+pub fn plan_runtimes(
+    java: &JavaVersion,
+    platforms: &[String],
+    archives: Option<&Path>,
+) -> Result<Vec<PlannedRuntime>> {
+    let mut runtimes = Vec::new();
+    let mut releases: Option<HashMap<String, HashMap<String, Vec<RuntimeRelease>>>> = None;
+    for platform in platforms {
+        let mut runtime = PlannedRuntime {
+            platform: platform.to_string(),
+            component: java.component.to_string(),
+            major_version: java.major_version,
+            path: format!("runtime/{}/{}", platform, java.component),
+            archive: None,
+            files: BTreeMap::new(),
+        };
+        let archive = archives.map(|v| v.join(format!("{}-{}.zip", java.component, platform)));
+        if let Some(archive) = archive.filter(|v| v.is_file()) {
+            runtime.archive = Some(archive);
+            runtimes.push(runtime);
+            continue;
+        }
+        if releases.is_none() {
+            releases = Some(get_latest_json(RUNTIME_MANIFEST_URL)?);
+        }
+        let release = releases
+            .as_ref()
+            .and_then(|v| v.get(platform))
+            .and_then(|v| v.get(&java.component))
+            .and_then(|v| v.first());
+        match release {
+            Some(release) => {
+                let manifest: RuntimeManifest = get_json(&release.manifest.url)?;
+                runtime.files = manifest.files;
+                runtimes.push(runtime);
+            }
+            None => log::warn!("No {} runtime for {}", java.component, platform),
+        }
+    }
+    Ok(runtimes)
+}
+
+/// Unpacks the runtime and returns the written paths relative to `base`.
+pub fn install_runtime(
+    pool: &DownloadPool,
+    runtime: &PlannedRuntime,
+    base: &Path,
+) -> Result<Vec<String>> {
+    let folder = base.join(&runtime.path);
+    create_dir_all(&folder).map_err(|e| GeneratorError::io(&folder, e))?;
+    match &runtime.archive {
+        Some(archive) => extract_archive(archive, &folder)?,
+        None => download_runtime(pool, runtime, base, &folder)?,
+    }
+    Ok(WalkDir::new(&folder)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .map(|e| relative(base, e.path()))
+        .collect())
+}
+
+fn extract_archive(archive: &Path, folder: &Path) -> Result<()> {
+    let file = File::open(archive).map_err(|e| GeneratorError::io(archive, e))?;
+    ZipArchive::new(file)
+        .and_then(|mut v| v.extract(folder))
+        .map_err(|e| GeneratorError::io(archive, e.into()))
+}
+
+fn download_runtime(
+    pool: &DownloadPool,
+    runtime: &PlannedRuntime,
+    base: &Path,
+    folder: &Path,
+) -> Result<()> {
+    for (path, entry) in &runtime.files {
+        if let RuntimeEntry::Directory = entry {
+            let directory = folder.join(path);
+            create_dir_all(&directory).map_err(|e| GeneratorError::io(&directory, e))?;
+        }
+    }
+    pool.download_all(base, &runtime.planned_files())?;
+    for (path, entry) in &runtime.files {
+        match entry {
+            RuntimeEntry::File {
+                executable: true, ..
+            } => set_executable(&folder.join(path))?,
+            RuntimeEntry::Link { target } => create_link(target, &folder.join(path))?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| GeneratorError::io(path, e))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_link(target: &str, path: &Path) -> Result<()> {
+    if path.symlink_metadata().is_ok() {
+        return Ok(());
+    }
+    std::os::unix::fs::symlink(target, path).map_err(|e| GeneratorError::io(path, e))
+}
+
+#[cfg(not(unix))]
+fn create_link(target: &str, path: &Path) -> Result<()> {
+    log::debug!("Skip link {} -> {}", path.display(), target);
+    Ok(())
+}
@@ -0,0 +1,33 @@
+// default MultiMC component order, used when a manifest doesn't provide one
+pub const FORGE_ORDER: i32 = 5;
+pub const LITELOADER_ORDER: i32 = 10;
+pub const OPTIFINE_ORDER: i32 = 20;
+
+/// LaunchWrapper tweakers of every installed component.
+/// Tweakers are passed to the game sorted by component order, a tweaker is added only once.
+#[derive(Default)]
+pub struct TweakerChain {
+    tweakers: Vec<(i32, String)>,
+}
+
+impl TweakerChain {
+    pub fn add(&mut self, order: i32, tweakers: &[String]) {
+        for tweaker in tweakers {
+            if !self.tweakers.iter().any(|(_, v)| v == tweaker) {
+                self.tweakers.push((order, tweaker.to_string()));
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tweakers.is_empty()
+    }
+
+    pub fn into_args(mut self) -> Vec<String> {
+        self.tweakers.sort_by_key(|(order, _)| *order);
+        self.tweakers
+            .into_iter()
+            .flat_map(|(_, tweaker)| vec!["--tweakClass".to_string(), tweaker])
+            .collect()
+    }
+}
@@ -1,8 +1,13 @@
+use crate::error::{GeneratorError, Result};
 use crate::minecraft::libraries::{AssetIndex, Downloads, Library};
-use anyhow::{Error, Result};
+use crate::minecraft::runtime::JavaVersion;
+use crate::util::{get_cached_text, get_latest_json};
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use std::str::FromStr;
 
+const VERSION_MANIFEST_URL: &str = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
+
 #[derive(Serialize, Deserialize)]
 pub struct VersionManifest {
     pub latest: Latest,
@@ -32,6 +37,34 @@ pub struct Libraries {
     pub asset_index: AssetIndex,
     pub downloads: Downloads,
     pub libraries: Vec<Library>,
+    #[serde(rename = "javaVersion")]
+    pub java_version: Option<JavaVersion>,
+    pub logging: Option<Logging>,
+    /// SHA-1 of the version JSON.
+    #[serde(skip)]
+    pub sha1: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Logging {
+    pub client: Option<LoggingConfig>,
+}
+
+/// Log4j configuration of the client, `argument` refers to the file as `${path}`.
+#[derive(Deserialize, Serialize)]
+pub struct LoggingConfig {
+    pub argument: String,
+    pub file: LoggingFile,
+    #[serde(rename = "type")]
+    pub config_type: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct LoggingFile {
+    pub id: String,
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -46,19 +79,19 @@ fn default_lib() -> String {
 }
 
 impl FromStr for Libraries {
-    type Err = Error;
+    type Err = GeneratorError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let manifest = reqwest::blocking::get(
-            "https://launchermeta.mojang.com/mc/game/version_manifest.json",
-        )?
-        .json::<VersionManifest>()?;
+    fn from_str(s: &str) -> Result<Self> {
+        let manifest = get_latest_json::<VersionManifest>(VERSION_MANIFEST_URL)?;
         let version = manifest
             .versions
             .iter()
             .find(|v| v.id.eq(s))
-            .ok_or(anyhow::anyhow!("Incorrect minecraft version"))?;
-        let libs = reqwest::blocking::get(&version.url)?.json::<Libraries>()?;
+            .ok_or_else(|| GeneratorError::unsupported_version("minecraft", s))?;
+        let json = get_cached_text(&version.url)?;
+        let mut libs = serde_json::from_str::<Libraries>(&json)
+            .map_err(|e| GeneratorError::metadata(&version.url, e))?;
+        libs.sha1 = hex::encode(Sha1::digest(json.as_bytes()));
         Ok(libs)
     }
 }
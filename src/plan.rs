@@ -0,0 +1,155 @@
+use crate::minecraft::libraries::File as LibraryFile;
+use crate::minecraft::runtime::PlannedRuntime;
+use launcher_api::profile::Profile;
+use serde::Serialize;
+use std::fmt;
+
+/// File to download, `path` is relative to the output folder.
+#[derive(Serialize, Clone)]
+pub struct PlannedFile {
+    pub url: String,
+    pub path: String,
+    pub sha1: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl PlannedFile {
+    /// Library resolved from a maven repository, its hash isn't known up front.
+    pub fn library(url: String, path: &str) -> Self {
+        PlannedFile {
+            url,
+            path: format!("libraries/{}", path),
+            sha1: None,
+            size: None,
+        }
+    }
+
+    /// Library from a Mojang style manifest.
+    pub fn from_library(file: &LibraryFile, path: &str) -> Self {
+        PlannedFile {
+            url: file.url.to_string(),
+            path: format!("libraries/{}", path),
+            sha1: Some(file.sha1.to_string()),
+            size: file.size,
+        }
+    }
+}
+
+impl fmt::Display for PlannedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.url, self.path)?;
+        if let Some(sha1) = &self.sha1 {
+            write!(f, " sha1:{}", sha1)?;
+        }
+        if let Some(size) = self.size {
+            write!(f, " {} bytes", size)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything a generation downloads and writes, resolved before touching the output folder.
+#[derive(Serialize)]
+pub struct Plan {
+    pub profile: Profile,
+    /// SHA-1 of the version JSON.
+    pub version_sha1: String,
+    pub client: PlannedFile,
+    pub asset_index: PlannedFile,
+    pub assets: Vec<PlannedFile>,
+    pub libraries: Vec<PlannedFile>,
+    /// Files put in the libraries folder, but not in the profile, e.g. Forge maven files.
+    pub maven_files: Vec<PlannedFile>,
+    /// Jars extracted to `path`.
+    pub natives: Vec<PlannedFile>,
+    /// Local mod jars copied to the profile.
+    pub mods: Vec<PlannedFile>,
+    /// Log4j configuration referenced by the `-Dlog4j.configurationFile` JVM argument.
+    pub logging: Option<PlannedFile>,
+    /// Java runtimes of the `javaVersion` component, one per platform.
+    pub runtimes: Vec<PlannedRuntime>,
+    /// LiteLoader and OptiFine versions, e.g. `optifine HD_U_G5`.
+    pub addons: Vec<String>,
+}
+
+impl Plan {
+    /// Every file the plan downloads, copies or unpacks, runtime files included.
+    pub fn files(&self) -> Vec<PlannedFile> {
+        let mut files: Vec<PlannedFile> = std::iter::once(&self.client)
+            .chain(std::iter::once(&self.asset_index))
+            .chain(&self.assets)
+            .chain(&self.libraries)
+            .chain(&self.maven_files)
+            .chain(&self.natives)
+            .chain(&self.mods)
+            .chain(&self.logging)
+            .cloned()
+            .collect();
+        for runtime in &self.runtimes {
+            match &runtime.archive {
+                Some(archive) => files.push(PlannedFile {
+                    url: archive.to_string_lossy().into_owned(),
+                    path: runtime.path.to_string(),
+                    sha1: None,
+                    size: None,
+                }),
+                None => files.extend(runtime.planned_files()),
+            }
+        }
+        files
+    }
+
+    /// Files whose hash may be unknown until they are downloaded, e.g. maven libraries and mods.
+    pub fn files_mut(&mut self) -> impl Iterator<Item = &mut PlannedFile> {
+        std::iter::once(&mut self.client)
+            .chain(std::iter::once(&mut self.asset_index))
+            .chain(self.assets.iter_mut())
+            .chain(self.libraries.iter_mut())
+            .chain(self.maven_files.iter_mut())
+            .chain(self.natives.iter_mut())
+            .chain(self.mods.iter_mut())
+            .chain(self.logging.iter_mut())
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let profile = &self.profile;
+        writeln!(f, "Profile: {} ({})", profile.name, profile.version)?;
+        writeln!(f, "Server: {}:{}", profile.server_name, profile.server_port)?;
+        writeln!(f, "Main class: {}", profile.main_class)?;
+        if !self.addons.is_empty() {
+            writeln!(f, "Addons: {}", self.addons.join(", "))?;
+        }
+        writeln!(f, "Class path:")?;
+        for path in profile.class_path.iter().chain(&profile.libraries) {
+            writeln!(f, "  {}", path)?;
+        }
+        writeln!(f, "JVM arguments: {}", profile.jvm_args.join(" "))?;
+        writeln!(f, "Client arguments: {}", profile.client_args.join(" "))?;
+        for runtime in &self.runtimes {
+            write!(
+                f,
+                "Runtime: {} (Java {}) -> {}",
+                runtime.component, runtime.major_version, runtime.path
+            )?;
+            match &runtime.archive {
+                Some(archive) => writeln!(f, " from {}", archive.display())?,
+                None => writeln!(f, ", {} entries", runtime.files.len())?,
+            }
+        }
+        writeln!(f, "Downloads:")?;
+        let files = std::iter::once(&self.client)
+            .chain(std::iter::once(&self.asset_index))
+            .chain(&self.libraries)
+            .chain(&self.maven_files)
+            .chain(&self.natives)
+            .chain(&self.mods)
+            .chain(&self.logging)
+            .chain(&self.assets);
+        for file in files {
+            writeln!(f, "  {}", file)?;
+        }
+        Ok(())
+    }
+}
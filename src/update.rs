@@ -0,0 +1,129 @@
+use crate::artifact::Artifact;
+use crate::builder::{Loader, ProfileGenerator};
+use crate::error::{GeneratorError, Result};
+use crate::index::{read_profiles_in, FileIndex};
+use crate::lock::GenerationInputs;
+use launcher_api::profile::Profile;
+use std::collections::HashSet;
+use std::fs::{read_dir, remove_dir, remove_file};
+use std::path::Path;
+
+/// Loader of an existing profile, detected from its libraries.
+pub fn detect_loader(profile: &Profile) -> Loader {
+    let loader = profile.libraries.iter().find_map(|library| {
+        let parts: Vec<&str> = library.split('/').collect();
+        match parts.as_slice() {
+            ["net", "minecraftforge", "forge", version, _] => {
+                // forge versions look like 1.12.2-14.23.5.2855 or 1.7.10-10.13.4.1614-1.7.10
+                let version = version
+                    .trim_start_matches(&format!("{}-", profile.version))
+                    .trim_end_matches(&format!("-{}", profile.version));
+                Some(Loader::Forge(version.to_string()))
+            }
+            ["net", "fabricmc", "fabric-loader", version, _] => {
+                Some(Loader::Fabric(version.to_string()))
+            }
+            _ => None,
+        }
+    });
+    loader.unwrap_or(Loader::Vanilla)
+}
+
+/// Fails unless the profile is in a folder named after it, the update is written back there.
+pub fn check_profile_folder(profile_folder: &Path, profile: &Profile) -> Result<()> {
+    if profile_folder.file_name() != Some(profile.name.as_ref()) {
+        return Err(GeneratorError::config(
+            profile_folder.display(),
+            format!("folder name must match profile name {}", profile.name),
+        ));
+    }
+    Ok(())
+}
+
+/// Adds the addons, mods, libraries and JVM arguments recorded in the profile lockfile.
+pub fn restore_inputs(
+    mut generator: ProfileGenerator,
+    inputs: GenerationInputs,
+) -> Result<ProfileGenerator> {
+    if let Some(liteloader) = &inputs.liteloader {
+        generator = generator.liteloader(liteloader);
+    }
+    if let Some(optifine) = inputs.optifine {
+        generator = generator.optifine(optifine);
+    }
+    for repository in &inputs.repositories {
+        generator = generator.repository(repository);
+    }
+    for library in &inputs.extra_libraries {
+        generator = generator.extra_library(library.parse::<Artifact>()?);
+    }
+    for arg in &inputs.jvm_args {
+        generator = generator.jvm_arg(arg);
+    }
+    for path in inputs.mods {
+        generator = generator.mod_file(path);
+    }
+    generator = generator.runtime_platforms(inputs.runtime_platforms);
+    if let Some(folder) = inputs.runtime_archives {
+        generator = generator.runtime_archives(folder);
+    }
+    Ok(generator)
+}
+
+/// Files under `libraries` a profile uses, including the ones only listed in its index.
+pub fn library_files(profile: &Profile, index: Option<&FileIndex>) -> HashSet<String> {
+    let mut files: HashSet<String> = profile
+        .libraries
+        .iter()
+        .map(|v| format!("libraries/{}", v))
+        .collect();
+    if let Some(index) = index {
+        files.extend(
+            index
+                .files
+                .iter()
+                .filter(|v| v.path.starts_with("libraries/"))
+                .map(|v| v.path.to_string()),
+        );
+    }
+    files
+}
+
+/// Removes library files the old profile used that no profile in `base` references anymore.
+/// Nothing is removed if a profile or index in `base` can't be read.
+pub fn remove_unreferenced(base: &Path, old_files: HashSet<String>) -> Result<Vec<String>> {
+    let mut referenced = HashSet::new();
+    for (_, profile, index) in read_profiles_in(base)? {
+        referenced.extend(library_files(&profile, index.as_ref()));
+    }
+    let mut unused: Vec<String> = old_files.difference(&referenced).cloned().collect();
+    unused.sort();
+    let mut removed = Vec::new();
+    for path in unused {
+        let file = base.join(&path);
+        if let Err(err) = remove_file(&file) {
+            log::warn!("Can't remove {}: {}", path, err);
+            continue;
+        }
+        remove_empty_parents(&base.join("libraries"), &file);
+        removed.push(path);
+    }
+    Ok(removed)
+}
+
+/// Removes empty folders left after a file removal, up to `root`.
+pub fn remove_empty_parents(root: &Path, file: &Path) {
+    let mut folder = file.parent();
+    while let Some(path) = folder {
+        if path == root || !path.starts_with(root) {
+            break;
+        }
+        let is_empty = read_dir(path)
+            .map(|mut v| v.next().is_none())
+            .unwrap_or(false);
+        if !is_empty || remove_dir(path).is_err() {
+            break;
+        }
+        folder = path.parent();
+    }
+}
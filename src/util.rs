@@ -1,38 +1,84 @@
-use crate::artifact::Artifact;
+use crate::cache::{get_immutable_text, get_latest_text};
+use crate::error::{GeneratorError, Result};
 use crate::minecraft::assets::Assets;
-use crate::minecraft::libraries::File as LibraryFile;
-use anyhow::Result;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::File;
 use std::path::Path;
 
-pub fn jar_url(base_path: &Path, file: &LibraryFile) -> (String, String) {
-    let url = file.url.clone();
-    (url, base_path.to_str().unwrap().to_string())
+pub fn asset_url(hash: &str) -> String {
+    format!(
+        "http://resources.download.minecraft.net/{}/{}",
+        &hash[0..2],
+        hash
+    )
 }
 
 pub fn get_assets(url: &str) -> Result<Assets> {
-    let assets = reqwest::blocking::get(url)?.json::<Assets>()?;
-    Ok(assets)
+    get_json(url)
 }
 
-pub fn generate_download_url(base_url: &str, name: &str) -> String {
-    format!("{}{}", base_url, &generate_lib_path(name))
+/// Fetches a text document, `None` if the server doesn't have it.
+pub fn get_optional_text(url: &str) -> Result<Option<String>> {
+    let response = reqwest::blocking::get(url).map_err(|e| GeneratorError::network(url, e))?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let text = response
+        .error_for_status()
+        .and_then(|v| v.text())
+        .map_err(|e| GeneratorError::network(url, e))?;
+    Ok(Some(text))
 }
 
-pub fn generate_lib_path(name: &str) -> String {
-    let artifact: Artifact = name.parse().unwrap();
-    artifact.to_path().to_str().unwrap().to_string()
+pub fn get_text(url: &str) -> Result<String> {
+    get_optional_text(url)?.ok_or_else(|| GeneratorError::network(url, StatusCode::NOT_FOUND))
 }
 
-pub fn get_yarn_url(version: &str) -> String {
-    format!(
-        "https://maven.fabricmc.net/net/fabricmc/intermediary/{ver}/intermediary-{ver}.jar",
-        ver = version
-    )
+/// Metadata text that doesn't change under its url, read from the metadata cache once fetched.
+pub fn get_cached_text(url: &str) -> Result<String> {
+    get_immutable_text(url)?.ok_or_else(|| GeneratorError::network(url, StatusCode::NOT_FOUND))
 }
 
-pub fn get_yarn_path(version: &str) -> String {
-    format!(
-        "net/fabricmc/intermediary/{ver}/intermediary-{ver}.jar",
-        ver = version
-    )
+fn parse_json<T: DeserializeOwned>(url: &str, text: Option<String>) -> Result<Option<T>> {
+    match text {
+        Some(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| GeneratorError::metadata(url, e)),
+        None => Ok(None),
+    }
+}
+
+/// Fetches a JSON document that doesn't change under its url, `None` if the server doesn't have it.
+pub fn get_optional_json<T: DeserializeOwned>(url: &str) -> Result<Option<T>> {
+    parse_json(url, get_immutable_text(url)?)
+}
+
+pub fn get_json<T: DeserializeOwned>(url: &str) -> Result<T> {
+    get_optional_json(url)?.ok_or_else(|| GeneratorError::network(url, StatusCode::NOT_FOUND))
+}
+
+/// Fetches a JSON document that changes over time, the cached copy is used when offline.
+pub fn get_latest_json<T: DeserializeOwned>(url: &str) -> Result<T> {
+    parse_json(url, get_latest_text(url)?)?
+        .ok_or_else(|| GeneratorError::network(url, StatusCode::NOT_FOUND))
+}
+
+pub fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = File::open(path).map_err(|e| GeneratorError::io(path, e))?;
+    serde_json::from_reader(file).map_err(|e| GeneratorError::metadata(path.display(), e))
+}
+
+pub fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let file = File::create(path).map_err(|e| GeneratorError::io(path, e))?;
+    serde_json::to_writer_pretty(file, value).map_err(|e| GeneratorError::io(path, e.into()))
+}
+
+/// Library name without version and classifier, e.g. `org.ow2.asm:asm`.
+pub fn library_key(name: &str) -> &str {
+    match name.match_indices(':').nth(1) {
+        Some((index, _)) => &name[..index],
+        None => name,
+    }
 }
@@ -1,26 +1,32 @@
-pub fn correct_forge_version(val: &str) -> Result<(), String> {
-    let url = format!(
-        "https://meta.multimc.org/v1/net.minecraftforge/{version}.json",
-        version = val
-    );
-    if reqwest::blocking::get(&url)
-        .map(|r| r.status() == 200)
-        .unwrap_or(false)
-    {
-        Ok(())
-    } else {
-        Err(String::from("Incorrect forge version"))
+use profile_generator::minecraft::fabric::FabricLoaderManifest;
+use profile_generator::minecraft::forge::ForgeManifest;
+use profile_generator::minecraft::liteloader::LiteLoaderManifest;
+use profile_generator::GeneratorError;
+use std::str::FromStr;
+
+/// Fetches the loader metadata, the generator later reads the same JSON from the metadata cache.
+fn check_version<T: FromStr<Err = GeneratorError>>(loader: &str, val: &str) -> Result<(), String> {
+    match val.parse::<T>() {
+        Ok(_) => Ok(()),
+        Err(GeneratorError::UnsupportedVersion { .. }) => {
+            Err(format!("Unknown {} version {}", loader, val))
+        }
+        Err(GeneratorError::Network { url, reason }) => Err(format!(
+            "Can't check {} version {}: {} is unavailable ({}) and not cached",
+            loader, val, url, reason
+        )),
+        Err(err) => Err(format!("Can't check {} version {}: {}", loader, val, err)),
     }
 }
 
+pub fn correct_forge_version(val: &str) -> Result<(), String> {
+    check_version::<ForgeManifest>("forge", val)
+}
+
 pub fn correct_fabric_version(val: &str) -> Result<(), String> {
-    let url = format!("https://maven.fabricmc.net/net/fabricmc/fabric-loader/{version}/fabric-loader-{version}.json", version = val);
-    if reqwest::blocking::get(&url)
-        .map(|r| r.status() == 200)
-        .unwrap_or(false)
-    {
-        Ok(())
-    } else {
-        Err(String::from("Incorrect fabroc version"))
-    }
+    check_version::<FabricLoaderManifest>("fabric", val)
+}
+
+pub fn correct_liteloader_version(val: &str) -> Result<(), String> {
+    check_version::<LiteLoaderManifest>("liteloader", val)
 }
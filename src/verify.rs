@@ -0,0 +1,243 @@
+use crate::artifact::Artifact;
+use crate::download::{check_file, DownloadPool};
+use crate::error::{GeneratorError, Result};
+use crate::generator::{client_file, download_natives, native_files};
+use crate::index::{read_index, read_profile};
+use crate::maven::Resolver;
+use crate::minecraft::assets::Assets;
+use crate::minecraft::version::Libraries;
+use crate::plan::PlannedFile;
+use crate::util::asset_url;
+use launcher_api::profile::Profile;
+use std::fmt;
+use std::fs::{create_dir_all, read_dir, remove_file, File};
+use std::path::Path;
+
+pub enum Problem {
+    MissingLibrary(String),
+    MissingClassPath(String),
+    MissingNatives(String),
+    MissingAssetIndex(String),
+    MissingAsset(String),
+    BadAsset(String),
+    MissingFile(String),
+    BadFile(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::MissingLibrary(path) => write!(f, "Library {} is missing", path),
+            Problem::MissingClassPath(path) => write!(f, "Class path entry {} is missing", path),
+            Problem::MissingNatives(path) => write!(f, "Natives folder {} is empty", path),
+            Problem::MissingAssetIndex(path) => write!(f, "Asset index {} is missing", path),
+            Problem::MissingAsset(hash) => write!(f, "Asset {} is missing", hash),
+            Problem::BadAsset(hash) => write!(f, "Asset {} has wrong hash or size", hash),
+            Problem::MissingFile(path) => write!(f, "Indexed file {} is missing", path),
+            Problem::BadFile(path) => write!(f, "Indexed file {} has wrong hash or size", path),
+        }
+    }
+}
+
+/// Checks that everything `profile.json` and `index.json` reference exists and is intact.
+pub fn verify(base: &Path, profile_folder: &Path) -> Result<Vec<Problem>> {
+    let profile = read_profile(profile_folder)?;
+    let mut problems = Vec::new();
+    for library in &profile.libraries {
+        if !base.join("libraries").join(library).is_file() {
+            problems.push(Problem::MissingLibrary(library.to_string()));
+        }
+    }
+    for entry in &profile.class_path {
+        if !profile_folder.join(entry).is_file() {
+            problems.push(Problem::MissingClassPath(entry.to_string()));
+        }
+    }
+    let native_folder = base.join("natives").join(&profile.version);
+    let has_natives = read_dir(&native_folder)
+        .map(|mut v| v.next().is_some())
+        .unwrap_or(false);
+    if !has_natives {
+        problems.push(Problem::MissingNatives(format!(
+            "natives/{}",
+            profile.version
+        )));
+    }
+    let assets_folder = base.join(&profile.assets_dir);
+    let asset_index = assets_folder
+        .join("indexes")
+        .join(format!("{}.json", profile.assets));
+    match File::open(&asset_index) {
+        Ok(file) => {
+            let assets: Assets = serde_json::from_reader(file)
+                .map_err(|e| GeneratorError::metadata(asset_index.display(), e))?;
+            let mut objects: Vec<_> = assets.objects.values().collect();
+            objects.sort_by(|a, b| a.hash.cmp(&b.hash));
+            for object in objects {
+                let path = format!("objects/{}/{}", &object.hash[0..2], object.hash);
+                let expected = PlannedFile {
+                    url: asset_url(&object.hash),
+                    path,
+                    sha1: Some(object.hash.to_string()),
+                    size: Some(object.size as u64),
+                };
+                let file = assets_folder.join(&expected.path);
+                if !file.is_file() {
+                    problems.push(Problem::MissingAsset(object.hash.clone()));
+                } else if check_file(&file, &expected).is_err() {
+                    problems.push(Problem::BadAsset(object.hash.clone()));
+                }
+            }
+        }
+        Err(_) => problems.push(Problem::MissingAssetIndex(format!(
+            "{}/indexes/{}.json",
+            profile.assets_dir, profile.assets
+        ))),
+    }
+    if let Ok(index) = read_index(profile_folder) {
+        for expected in index.files {
+            let file = base.join(&expected.path);
+            let planned = PlannedFile {
+                url: String::new(),
+                path: expected.path,
+                sha1: Some(expected.sha1),
+                size: Some(expected.size),
+            };
+            if !file.is_file() {
+                // missing libraries and assets are already reported
+                if !is_reported(&problems, &planned.path) {
+                    problems.push(Problem::MissingFile(planned.path));
+                }
+            } else if check_file(&file, &planned).is_err() {
+                problems.push(Problem::BadFile(planned.path));
+            }
+        }
+    }
+    Ok(problems)
+}
+
+fn is_reported(problems: &[Problem], path: &str) -> bool {
+    problems.iter().any(|v| match v {
+        Problem::MissingLibrary(library) => path == format!("libraries/{}", library),
+        Problem::MissingAsset(hash) => path.ends_with(hash.as_str()),
+        _ => false,
+    })
+}
+
+/// Problem `repair` couldn't fix.
+pub struct RepairFailure {
+    pub problem: Problem,
+    pub error: GeneratorError,
+}
+
+impl fmt::Display for RepairFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Can't repair: {}: {}", self.problem, self.error)
+    }
+}
+
+/// Outcome of `repair`.
+pub struct RepairReport {
+    /// Problems verification still finds after the repair.
+    pub remaining: Vec<Problem>,
+    pub failures: Vec<RepairFailure>,
+}
+
+/// Downloads what can be restored from the version manifest and maven repositories.
+/// A problem that can't be repaired doesn't stop the others, it is reported with its error.
+pub fn repair(
+    base: &Path,
+    profile_folder: &Path,
+    problems: Vec<Problem>,
+    resolver: &Resolver,
+) -> Result<RepairReport> {
+    let profile = read_profile(profile_folder)?;
+    let manifest = profile.version.parse::<Libraries>()?;
+    let pool = DownloadPool::default();
+    let mut failures = Vec::new();
+    for problem in problems {
+        log::info!("Repair: {}", problem);
+        if let Err(error) = repair_problem(&pool, base, &profile, &manifest, resolver, &problem) {
+            failures.push(RepairFailure { problem, error });
+        }
+    }
+    Ok(RepairReport {
+        remaining: verify(base, profile_folder)?,
+        failures,
+    })
+}
+
+fn repair_problem(
+    pool: &DownloadPool,
+    base: &Path,
+    profile: &Profile,
+    manifest: &Libraries,
+    resolver: &Resolver,
+    problem: &Problem,
+) -> Result<()> {
+    let libraries_folder = base.join("libraries");
+    match problem {
+        Problem::MissingLibrary(path) => repair_library(pool, base, path, manifest, resolver)?,
+        Problem::BadFile(path) | Problem::MissingFile(path) if path.starts_with("libraries/") => {
+            let path = path.trim_start_matches("libraries/");
+            let _ = remove_file(libraries_folder.join(path));
+            repair_library(pool, base, path, manifest, resolver)?;
+        }
+        Problem::MissingClassPath(path) if path == "minecraft.jar" => {
+            pool.download(base, &client_file(manifest, &profile.name)?)?
+        }
+        Problem::MissingNatives(_) => {
+            let native_folder = base.join("natives").join(&profile.version);
+            create_dir_all(&native_folder).map_err(|e| GeneratorError::io(&native_folder, e))?;
+            let natives = native_files(&manifest.libraries, &profile.version);
+            download_natives(pool, &natives, base, &native_folder)?;
+        }
+        Problem::MissingAssetIndex(path) => pool.download(
+            base,
+            &PlannedFile {
+                url: manifest.asset_index.url.to_string(),
+                path: path.to_string(),
+                sha1: Some(manifest.asset_index.sha1.to_string()),
+                size: None,
+            },
+        )?,
+        Problem::MissingAsset(hash) | Problem::BadAsset(hash) => {
+            let path = format!("{}/objects/{}/{}", profile.assets_dir, &hash[0..2], hash);
+            let _ = remove_file(base.join(&path));
+            pool.download(
+                base,
+                &PlannedFile {
+                    url: asset_url(hash),
+                    path,
+                    sha1: Some(hash.to_string()),
+                    size: None,
+                },
+            )?;
+        }
+        problem => log::warn!("Can't repair: {}", problem),
+    }
+    Ok(())
+}
+
+fn repair_library(
+    pool: &DownloadPool,
+    base: &Path,
+    path: &str,
+    manifest: &Libraries,
+    resolver: &Resolver,
+) -> Result<()> {
+    let vanilla = manifest
+        .libraries
+        .iter()
+        .filter_map(|v| v.downloads.artifact.as_ref())
+        .find(|v| v.path.as_deref() == Some(path));
+    let file = match vanilla {
+        Some(file) => PlannedFile::from_library(file, path),
+        None => {
+            let artifact = Artifact::from_path(path)
+                .ok_or_else(|| GeneratorError::LibraryNotFound(path.to_string()))?;
+            PlannedFile::library(resolver.locate(&artifact)?.0, path)
+        }
+    };
+    pool.download(base, &file)
+}
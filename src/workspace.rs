@@ -0,0 +1,121 @@
+use crate::config::ProfileConfig;
+use crate::error::{GeneratorError, Result};
+use launcher_api::profile::Profile;
+use serde::Deserialize;
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// Several profile definitions generated into one shared output folder.
+#[derive(Deserialize)]
+pub struct Workspace {
+    /// Folder with shared libraries, assets and natives, relative to the workspace file.
+    #[serde(default)]
+    pub output: PathBuf,
+    /// Metadata cache folder of profiles that don't declare their own, relative to the workspace file.
+    #[serde(default)]
+    pub cache: Option<PathBuf>,
+    /// Repositories of profiles that don't declare their own.
+    #[serde(default)]
+    pub repositories: Vec<String>,
+    /// JVM arguments put before the ones of each profile.
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    #[serde(default, rename = "profile")]
+    pub profiles: Vec<ProfileConfig>,
+}
+
+impl Workspace {
+    /// Reads the workspace and applies shared settings to its profiles.
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = read_to_string(path).map_err(|e| GeneratorError::io(path, e))?;
+        let mut workspace: Workspace =
+            toml::from_str(&text).map_err(|e| GeneratorError::config(path.display(), e))?;
+        let folder = path.parent().unwrap_or_else(|| Path::new(""));
+        workspace.output = folder.join(&workspace.output);
+        workspace.cache = workspace.cache.map(|v| folder.join(v));
+        for profile in &mut workspace.profiles {
+            profile.resolve_paths(folder);
+            if profile.cache.is_none() {
+                profile.cache = workspace.cache.clone();
+            }
+            if profile.repositories.is_empty() {
+                profile.repositories = workspace.repositories.clone();
+            }
+            let mut jvm_args = workspace.jvm_args.clone();
+            for arg in profile.jvm_args.drain(..) {
+                if !jvm_args.contains(&arg) {
+                    jvm_args.push(arg);
+                }
+            }
+            profile.jvm_args = jvm_args;
+        }
+        Ok(workspace)
+    }
+}
+
+/// Difference between the profile on disk and the generated one.
+pub struct ProfileChanges {
+    pub name: String,
+    pub old_version: Option<String>,
+    pub new_version: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub main_class: Option<String>,
+}
+
+impl ProfileChanges {
+    pub fn new(old: Option<&Profile>, new: &Profile) -> Self {
+        let old_libraries: &[String] = old.map(|v| v.libraries.as_slice()).unwrap_or_default();
+        ProfileChanges {
+            name: new.name.to_string(),
+            old_version: old.map(|v| v.version.to_string()),
+            new_version: new.version.to_string(),
+            added: new
+                .libraries
+                .iter()
+                .filter(|v| !old_libraries.contains(v))
+                .cloned()
+                .collect(),
+            removed: old_libraries
+                .iter()
+                .filter(|v| !new.libraries.contains(v))
+                .cloned()
+                .collect(),
+            main_class: match old {
+                Some(old) if old.main_class == new.main_class => None,
+                _ => Some(new.main_class.to_string()),
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.old_version.as_ref() == Some(&self.new_version)
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.main_class.is_none()
+    }
+}
+
+impl fmt::Display for ProfileChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.old_version {
+            None => return writeln!(f, "{}: new profile ({})", self.name, self.new_version),
+            Some(_) if self.is_empty() => return writeln!(f, "{}: unchanged", self.name),
+            Some(old_version) if old_version != &self.new_version => {
+                writeln!(f, "{}: {} -> {}", self.name, old_version, self.new_version)?
+            }
+            Some(_) => writeln!(f, "{}:", self.name)?,
+        }
+        if let Some(main_class) = &self.main_class {
+            writeln!(f, "  main class {}", main_class)?;
+        }
+        for library in &self.added {
+            writeln!(f, "  + {}", library)?;
+        }
+        for library in &self.removed {
+            writeln!(f, "  - {}", library)?;
+        }
+        Ok(())
+    }
+}